@@ -0,0 +1,192 @@
+//! The error every export throws.
+//!
+//! Failures cross into JS as a `NeoDemoError`, a subclass of `Error` with a stable `kind` and,
+//! when known, the step and source line involved. `message` keeps the `"<what> error: …"`
+//! prefixes, so it reads the same in a log. The class itself is exported through
+//! `neo_demo_error_class()` for `instanceof` checks.
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+/// Stable category of a failure; JS sees it as the snake_case `kind` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NeoDemoErrorKind {
+    /// Malformed input: JSON, config objects, ELF/proof/key bytes, ROM words that don't decode,
+    /// out-of-range arguments.
+    Parse,
+    /// Well-formed input that does not fit what it is used with: a step of the wrong size, a
+    /// proof or keys for another circuit, a program that does not fit the memory map, a call
+    /// that needs a program before one was assembled.
+    Shape,
+    /// Proving failed; for RV32 guests this includes the native run faulting or not halting.
+    /// `neo-fold` reports an unsatisfied step witness as plain text, so it arrives here (or as
+    /// `Shape` when a step is added).
+    Prove,
+    Verify,
+    SpartanSetup,
+    SpartanProve,
+    SpartanVerify,
+    /// Mini-asm errors, including a program that assembles to nothing.
+    Assemble,
+    /// Encoding a result, proof or keys failed.
+    Serialize,
+}
+
+impl NeoDemoErrorKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            NeoDemoErrorKind::Parse => "parse",
+            NeoDemoErrorKind::Shape => "shape",
+            NeoDemoErrorKind::Prove => "prove",
+            NeoDemoErrorKind::Verify => "verify",
+            NeoDemoErrorKind::SpartanSetup => "spartan_setup",
+            NeoDemoErrorKind::SpartanProve => "spartan_prove",
+            NeoDemoErrorKind::SpartanVerify => "spartan_verify",
+            NeoDemoErrorKind::Assemble => "assemble",
+            NeoDemoErrorKind::Serialize => "serialize",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NeoDemoError {
+    pub(crate) kind: NeoDemoErrorKind,
+    pub(crate) message: String,
+    /// Fold step (or, for RV32 runs, executed instruction) the failure refers to.
+    pub(crate) step: Option<usize>,
+    /// 1-based source line (mini-asm).
+    pub(crate) line: Option<usize>,
+}
+
+impl NeoDemoError {
+    pub(crate) fn new(kind: NeoDemoErrorKind, message: impl Into<String>) -> Self {
+        NeoDemoError {
+            kind,
+            message: message.into(),
+            step: None,
+            line: None,
+        }
+    }
+
+    pub(crate) fn parse(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Parse, message)
+    }
+
+    pub(crate) fn shape(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Shape, message)
+    }
+
+    pub(crate) fn prove(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Prove, message)
+    }
+
+    pub(crate) fn verify(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Verify, message)
+    }
+
+    pub(crate) fn spartan_setup(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::SpartanSetup, message)
+    }
+
+    pub(crate) fn spartan_prove(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::SpartanProve, message)
+    }
+
+    pub(crate) fn spartan_verify(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::SpartanVerify, message)
+    }
+
+    pub(crate) fn assemble(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Assemble, message)
+    }
+
+    pub(crate) fn serialize(message: impl Into<String>) -> Self {
+        Self::new(NeoDemoErrorKind::Serialize, message)
+    }
+
+    pub(crate) fn at_step(mut self, step: usize) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub(crate) fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl fmt::Display for NeoDemoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[wasm_bindgen(inline_js = r#"
+export class NeoDemoError extends Error {
+    constructor(kind, message, step, line) {
+        super(message);
+        this.name = "NeoDemoError";
+        this.kind = kind;
+        this.step = step;
+        this.line = line;
+    }
+}
+
+export function neoDemoErrorClass() {
+    return NeoDemoError;
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = NeoDemoError)]
+    type JsNeoDemoError;
+
+    #[wasm_bindgen(typescript_type = "NeoDemoErrorConstructor")]
+    pub type NeoDemoErrorClass;
+
+    #[wasm_bindgen(js_name = neoDemoErrorClass)]
+    fn neo_demo_error_class_js() -> NeoDemoErrorClass;
+
+    #[wasm_bindgen(constructor, js_class = "NeoDemoError")]
+    fn new(kind: &str, message: &str, step: Option<u32>, line: Option<u32>) -> JsNeoDemoError;
+}
+
+/// The `NeoDemoError` class, so JS can test `e instanceof neo_demo_error_class()`.
+#[wasm_bindgen]
+pub fn neo_demo_error_class() -> NeoDemoErrorClass {
+    neo_demo_error_class_js()
+}
+
+impl From<NeoDemoError> for JsValue {
+    fn from(e: NeoDemoError) -> JsValue {
+        let index = |i: Option<usize>| i.map(|i| i as u32);
+        JsNeoDemoError::new(e.kind.as_str(), &e.message, index(e.step), index(e.line)).into()
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const NEO_DEMO_ERROR_TYPES: &str = r#"
+export type NeoDemoErrorKind =
+    | "parse"
+    | "shape"
+    | "prove"
+    | "verify"
+    | "spartan_setup"
+    | "spartan_prove"
+    | "spartan_verify"
+    | "assemble"
+    | "serialize";
+
+/** What every export throws; `name` is always `"NeoDemoError"`. */
+export interface NeoDemoError extends Error {
+    kind: NeoDemoErrorKind;
+    step?: number;
+    line?: number;
+}
+
+/** The class behind `NeoDemoError`, returned by `neo_demo_error_class()`. */
+export interface NeoDemoErrorConstructor {
+    new (kind: NeoDemoErrorKind, message: string, step?: number, line?: number): NeoDemoError;
+    readonly prototype: NeoDemoError;
+}
+"#;
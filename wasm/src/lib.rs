@@ -1,14 +1,35 @@
+use std::collections::{BTreeMap, HashMap};
+
 use wasm_bindgen::prelude::*;
 
+mod error;
 mod riscv_asm;
+mod rv32;
+mod rv32_disasm;
+mod rv32_elf;
+mod rv32_exec;
+mod rv32_syscall;
+mod rv32_trace;
+mod spartan_keys;
+mod ts_types;
 
-use js_sys::Date;
 use neo_fold::test_export::{
     estimate_proof, folding_summary, parse_test_export_json, run_test_export, TestExportSession,
 };
-use neo_math::F;
+use error::NeoDemoError;
 use neo_spartan_bridge::circuit::FoldRunWitness;
-use p3_field::PrimeCharacteristicRing;
+use neo_fold::riscv_trace_shard::Rv32TraceWiringRun;
+use riscv_asm::{Rv32AsmLayout, Rv32Program};
+use rv32::{
+    merge_ram_init, Rv32Layout, Rv32OutputRegion, Rv32ProofSummary, Rv32ProveConfig, Rv32PublicStatement, Rv32RamInit,
+    Rv32RamUsage, Rv32RunResult, Rv32SpartanResult, Rv32WiringInputs, DEFAULT_EXEC_MAX_STEPS,
+};
+use rv32_disasm::Rv32Listing;
+use rv32_exec::{Rv32ExecReport, Rv32HaltReason, Rv32Machine, Rv32StepRecord, Rv32StepRecords};
+use rv32_trace::Rv32TraceRows;
+use spartan_keys::{circuit_digest, spartan_keys_id, SpartanKeys};
+use ts_types::{mirror, to_js, CircuitSummary, FoldStepMs, FoldingSummary, ParamsSummary, ProofEstimate, SetupTimingsMs};
+use tsify::Tsify;
 
 #[wasm_bindgen]
 pub fn init_panic_hook() {
@@ -26,148 +47,693 @@ pub fn init_thread_pool(num_threads: usize) -> js_sys::Promise {
 #[wasm_bindgen]
 pub fn prove_verify_test_export_json(json: &str) -> Result<JsValue, JsValue> {
     let export = parse_test_export_json(json)
-        .map_err(|e| JsValue::from_str(&format!("parse error: {e}")))?;
+        .map_err(|e| NeoDemoError::parse(format!("parse error: {e}")))?;
 
-    let result =
-        run_test_export(&export).map_err(|e| JsValue::from_str(&format!("run error: {e}")))?;
+    let result = run_test_export(&export)
+        .map_err(|e| NeoDemoError::prove(format!("run error: {e}")))?;
 
-    serde_wasm_bindgen::to_value(&result)
-        .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    serde_wasm_bindgen::to_value(&result).map_err(|e| NeoDemoError::serialize(format!("serialize error: {e}")).into())
 }
 
-#[derive(serde::Serialize)]
-struct Rv32FibRunResult {
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32FibRunResult {
     n: u32,
-    expected: u32,
+    /// RAM[0x100] after the native run; this is the public output the proof claims.
+    output: u32,
+    /// Caller-supplied expectation, if any, and whether `output` matches it.
+    #[tsify(optional)]
+    expected: Option<u32>,
+    #[tsify(optional)]
+    output_matches: Option<bool>,
+    ram: Rv32RamUsage,
     verify_ok: bool,
     prove_ms: f64,
     verify_ms: f64,
+    #[tsify(optional)]
     trace_len: Option<usize>,
     folds: usize,
     ccs_constraints: usize,
     ccs_variables: usize,
+    #[tsify(optional)]
     shout_lookups: Option<usize>,
-    spartan: Option<Rv32FibSpartanResult>,
+    #[tsify(optional)]
+    spartan: Option<Rv32SpartanResult>,
 }
 
-#[derive(serde::Serialize)]
-struct Rv32FibSpartanResult {
-    prove_ms: f64,
-    verify_ms: f64,
-    verify_ok: bool,
-    snark_bytes: usize,
-    snark: Vec<u8>,
+/// Prove+verify an arbitrary RV32 mini-asm program under trace-wiring mode.
+///
+/// `config` is a plain JS object:
+/// `{ program_base?, memory_map?, syscalls?, xlen?, ram_init?: [{addr, value}], inputs?: [{addr, words, public?}],
+///    outputs?: [{addr, expected}], output_regions?: [{addr, words}], chunk_rows, ram_bytes?,
+///    max_steps?, do_spartan?, includes?: { name: source } }`.
+/// `memory_map` is `{ rom: {base, size}, ram: {base, size}, stack?, io? }`: the program is
+/// assembled at `rom.base` with `.data` at `ram.base`, and loads/stores must stay in `ram`,
+/// `stack` or `io`.
+/// `syscalls` is `{ input?: [u32], journal_words? }` and turns `ecall` into a syscall selected by
+/// `a7` (halt, read input, commit to the journal, debug print); the I/O block lives at `io.base`.
+/// Every output claim is checked by the proof; output regions are claimed with the values a
+/// native run leaves there. The result's `statement` lists the program digest, public inputs
+/// and outputs the proof attests to, plus the exit code and journal of a syscall guest, and
+/// `io` reports them with the debug prints. The guest halts via `ecall`.
+#[wasm_bindgen]
+pub fn prove_verify_rv32_asm(asm: &str, config: JsValue) -> Result<<Rv32RunResult as Tsify>::JsType, JsValue> {
+    let config: Rv32ProveConfig = serde_wasm_bindgen::from_value(config)
+        .map_err(|e| NeoDemoError::parse(format!("config error: {e}")))?;
+    config.validate()?;
+    let program = riscv_asm::assemble_rv32_mini_asm(asm, &config.includes, &config.layout().asm_layout())?;
+
+    let result = rv32::prove_verify_rv32(&program, &config)?;
+
+    to_js(&result)
+}
+
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32AsmCheck {
+    ok: bool,
+    rom_bytes: usize,
+    diagnostics: Vec<riscv_asm::AsmDiagnostic>,
+}
+
+/// Optional `{ name: source }` map of files for `.include`; `undefined`/`null` means none.
+fn includes_from_js(includes: JsValue) -> Result<HashMap<String, String>, JsValue> {
+    if includes.is_undefined() || includes.is_null() {
+        return Ok(HashMap::new());
+    }
+    serde_wasm_bindgen::from_value(includes).map_err(|e| NeoDemoError::parse(format!("includes error: {e}")).into())
 }
 
-fn fib_u32(n: u32) -> u32 {
-    let mut n = n;
-    let mut a = 0u32;
-    let mut b = 1u32;
-    while n > 0 {
-        let next = a.wrapping_add(b);
-        a = b;
-        b = next;
-        n -= 1;
+/// Optional `{ program_base?, memory_map?, syscalls? }` placement; `undefined`/`null` means address 0 with
+/// `.data` at `DEFAULT_DATA_BASE`.
+fn layout_from_js(layout: JsValue) -> Result<Rv32Layout, JsValue> {
+    if layout.is_undefined() || layout.is_null() {
+        return Ok(Rv32Layout::default());
     }
-    a
+    let layout: Rv32Layout = serde_wasm_bindgen::from_value(layout)
+        .map_err(|e| NeoDemoError::parse(format!("layout error: {e}")))?;
+    layout.validate()?;
+    Ok(layout)
+}
+
+/// Assemble mini-asm and report every diagnostic instead of stopping at the first error:
+/// `{ ok, rom_bytes, diagnostics: [{ line, column_start, column_end, severity, code, message, hint }] }`.
+/// Columns are 1-based with an exclusive end, ready for an editor to underline. `includes` is an
+/// optional `{ name: source }` map for `.include`, and `layout` an optional
+/// `{ program_base?, memory_map?, syscalls? }` (as in `prove_verify_rv32_asm`).
+#[wasm_bindgen]
+pub fn check_rv32_asm(
+    asm: &str,
+    includes: JsValue,
+    layout: JsValue,
+) -> Result<<Rv32AsmCheck as Tsify>::JsType, JsValue> {
+    let includes = includes_from_js(includes)?;
+    let layout = layout_from_js(layout)?;
+    let out = riscv_asm::assemble_rv32(asm, &includes, &layout.asm_layout());
+    let result = Rv32AsmCheck {
+        ok: out.program.is_some(),
+        rom_bytes: out.program.map(|p| p.rom.len()).unwrap_or(0),
+        diagnostics: out.diagnostics,
+    };
+    to_js(&result)
+}
+
+/// Disassemble a raw RV32 ROM image (little-endian words at `program_base`, default 0) into a
+/// listing: `[{ addr, word, word_hex, label, text, target, target_label, line, source }]`.
+#[wasm_bindgen]
+pub fn disassemble_rv32(bytes: &[u8], program_base: Option<u32>) -> Result<<Rv32Listing as Tsify>::JsType, JsValue> {
+    let rows =
+        rv32_disasm::disassemble_rv32(bytes, program_base.unwrap_or(0), &[], &[], None).map_err(NeoDemoError::parse)?;
+    to_js(&Rv32Listing(rows))
+}
+
+/// Assemble mini-asm and return its listing with label names and originating source lines, for
+/// a side-by-side view of what the assembler produced. Addresses follow `layout`, an optional
+/// `{ program_base?, memory_map?, syscalls? }`.
+#[wasm_bindgen]
+pub fn assemble_rv32_listing(
+    asm: &str,
+    includes: JsValue,
+    layout: JsValue,
+) -> Result<<Rv32Listing as Tsify>::JsType, JsValue> {
+    let includes = includes_from_js(includes)?;
+    let layout = layout_from_js(layout)?;
+    let asm_layout = layout.asm_layout();
+    let program = riscv_asm::assemble_rv32_mini_asm(asm, &includes, &asm_layout)?;
+    let rows = rv32_disasm::disassemble_rv32(
+        &program.rom,
+        asm_layout.text_base,
+        &program.symbols,
+        &program.lines,
+        Some(asm),
+    )
+    .map_err(NeoDemoError::assemble)?;
+    to_js(&Rv32Listing(rows))
+}
+
+/// Assemble `asm` (resolving `.include` from `includes`) at `layout`'s program base with an optional
+/// `[{ addr, value }]` RAM init applied over its `.data`; a memory map bounds loads/stores.
+fn native_machine(
+    asm: &str,
+    includes: &HashMap<String, String>,
+    ram_init: JsValue,
+    layout: &Rv32Layout,
+) -> Result<(Rv32Program, Rv32Machine), JsValue> {
+    let mut ram_init: Vec<Rv32RamInit> = if ram_init.is_undefined() || ram_init.is_null() {
+        Vec::new()
+    } else {
+        serde_wasm_bindgen::from_value(ram_init).map_err(|e| NeoDemoError::parse(format!("ram_init error: {e}")))?
+    };
+    if let Some(init) = ram_init.iter().find(|i| i.addr & 3 != 0) {
+        return Err(NeoDemoError::parse(format!(
+            "ram_init error: address {:#x} is not 4-byte aligned",
+            init.addr
+        ))
+        .into());
+    }
+    ram_init.extend(layout.io_init());
+    let asm_layout = layout.asm_layout();
+    let program = riscv_asm::assemble_rv32_mini_asm(asm, includes, &asm_layout)?;
+    layout.check_program(&program)?;
+    let machine = Rv32Machine::new(asm_layout.text_base, &program.rom, &merge_ram_init(&program, &ram_init)?)
+        .map_err(|e| NeoDemoError::parse(format!("execute error: {e}")))?
+        .with_ram_regions(layout.ram_regions())
+        .with_syscalls(layout.syscall_shim(&program));
+    Ok((program, machine))
+}
+
+/// Run mini-asm natively, without proving, to check that it terminates and what it leaves behind
+/// before paying for a proof.
+///
+/// `ram_init` is an optional `[{ addr, value }]` array applied over the program's `.data`,
+/// `layout` an optional `{ program_base?, memory_map?, syscalls? }` and `includes` an optional
+/// `{ name: source }` map for `.include`; `max_steps = 0` uses the default budget. Returns
+/// `{ steps, halt: { kind: "ecall" | "max_steps" | "invalid_pc" | "unsupported" | "out_of_bounds"
+///    | "unknown_syscall" | "journal_full", .. }, final_pc, regs, memory: [{ addr, value }], trace_len, folds,
+///    io }`, where `memory` holds every word a load/store touched, `trace_len`/`folds` predict the
+/// prover's trace for `chunk_rows`, and `io` is `{ exit_code, input_read, journal, prints }` for a
+/// syscall guest.
+#[wasm_bindgen]
+pub fn execute_rv32(
+    asm: &str,
+    ram_init: JsValue,
+    max_steps: usize,
+    chunk_rows: usize,
+    layout: JsValue,
+    includes: JsValue,
+) -> Result<<Rv32ExecReport as Tsify>::JsType, JsValue> {
+    let (_, mut machine) = native_machine(asm, &includes_from_js(includes)?, ram_init, &layout_from_js(layout)?)?;
+    let max_steps = if max_steps > 0 { max_steps } else { DEFAULT_EXEC_MAX_STEPS };
+    let trace = machine.run(max_steps);
+
+    let report = Rv32ExecReport::new(&machine, &trace, chunk_rows);
+    to_js(&report)
+}
+
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32ElfRunResult {
+    entry: u32,
+    program_base: u32,
+    rom_bytes: usize,
+    ram_init_words: usize,
+    run: rv32::Rv32RunResult,
+}
+
+/// Prove+verify an RV32 ELF executable (e.g. built with `--target riscv32im-unknown-none-elf`).
+///
+/// `config` has the same shape as for `prove_verify_rv32_asm`; `program_base` is taken from the
+/// ELF, and its initialized data is applied before `config.ram_init`.
+#[wasm_bindgen]
+pub fn prove_verify_rv32_elf(elf: &[u8], config: JsValue) -> Result<<Rv32ElfRunResult as Tsify>::JsType, JsValue> {
+    let mut config: Rv32ProveConfig = serde_wasm_bindgen::from_value(config)
+        .map_err(|e| NeoDemoError::parse(format!("config error: {e}")))?;
+    let image = rv32_elf::load_rv32_elf(elf).map_err(NeoDemoError::parse)?;
+    config.program_base = Some(image.program_base as u64);
+    config.validate_elf()?;
+
+    let run = rv32::prove_verify_rv32(&image.program, &config)?;
+
+    let result = Rv32ElfRunResult {
+        entry: image.entry,
+        program_base: image.program_base,
+        rom_bytes: image.program.rom.len(),
+        ram_init_words: image.program.ram_init.len(),
+        run,
+    };
+    to_js(&result)
 }
 
 /// Prove+verify the RV32 Fibonacci program under trace-wiring mode.
 ///
 /// Expected guest semantics:
 /// - reads `n` from RAM[0x104] (u32)
-/// - writes `fib(n)` to RAM[0x100] (u32)
+/// - writes its result to RAM[0x100] (u32)
 /// - halts via `ecall` (treated as `Halt` in this VM)
+///
+/// The guest runs natively first and the proof claims whatever it left at RAM[0x100], so any
+/// program with this I/O shape can be proven. `expected` is only compared against that output.
+/// `ram_bytes` is the RAM size: initialized words and guest loads/stores outside it are errors
+/// naming the address (and PC), and the result's `ram` reports how much of it the run used along
+/// with the proof's CCS size and timings. The bound is checked natively before proving; the proof
+/// itself does not enforce it. `includes` is an optional `{ name: source }` map for `.include`.
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn prove_verify_rv32_trace_fibonacci_asm(
     asm: &str,
     n: u32,
-    _ram_bytes: usize,
+    ram_bytes: usize,
     chunk_rows: usize,
     max_steps: usize,
     do_spartan: bool,
-) -> Result<JsValue, JsValue> {
-    let program_bytes = riscv_asm::assemble_rv32_mini_asm(asm).map_err(|e| JsValue::from_str(&e))?;
-    if program_bytes.is_empty() {
-        return Err(JsValue::from_str("assembled program is empty"));
-    }
-
-    let expected = fib_u32(n);
-    let expected_f = F::from_u64(expected as u64);
-
-    let mut run = {
-        let mut b = neo_fold::riscv_trace_shard::Rv32TraceWiring::from_rom(/*program_base=*/ 0, &program_bytes)
-            .xlen(32)
-            .ram_init_u32(/*addr=*/ 0x104, n)
-            .chunk_rows(chunk_rows)
-            .shout_auto_minimal()
-            .output(/*output_addr=*/ 0x100, /*expected_output=*/ expected_f);
-        if max_steps > 0 {
-            b = b.max_steps(max_steps);
-        }
-        b.prove().map_err(|e| JsValue::from_str(&format!("prove error: {e}")))?
-    };
+    expected: Option<u32>,
+    includes: JsValue,
+) -> Result<<Rv32FibRunResult as Tsify>::JsType, JsValue> {
+    let includes = includes_from_js(includes)?;
+    let program = riscv_asm::assemble_rv32_mini_asm(asm, &includes, &Rv32AsmLayout::default())?;
+    let mut config = fib_config(n, ram_bytes as u64, chunk_rows);
+    config.max_steps = max_steps;
+    config.do_spartan = do_spartan;
+    config.includes = includes;
 
-    let prove_ms = run.prove_duration().as_secs_f64() * 1000.0;
-
-    run.verify().map_err(|e| JsValue::from_str(&format!("verify error: {e}")))?;
-    let verify_ok = true;
-    let verify_ms = run
-        .verify_duration()
-        .map(|d| d.as_secs_f64() * 1000.0)
-        .unwrap_or(0.0);
-
-    let trace_len = Some(run.trace_len());
-    let folds = run.fold_count();
-    let ccs_constraints = run.ccs_num_constraints();
-    let ccs_variables = run.ccs_num_variables();
-    let shout_lookups = Some(run.exec_table().rows.iter().map(|r| r.shout_events.len()).sum());
-
-    let spartan = if do_spartan {
-        let acc_init = &[];
-        let witness = fold_run_witness_placeholder(run.proof());
-        let prove_start = Date::now();
-        let keypair = neo_spartan_bridge::setup_fold_run(run.params(), run.ccs(), acc_init, run.proof(), witness.clone())
-            .map_err(|e| JsValue::from_str(&format!("spartan setup error: {e}")))?;
-        let spartan =
-            neo_spartan_bridge::prove_fold_run(&keypair.pk, run.params(), run.ccs(), acc_init, run.proof(), witness)
-                .map_err(|e| JsValue::from_str(&format!("spartan prove error: {e}")))?;
-        let prove_ms = Date::now() - prove_start;
-
-        let verify_start = Date::now();
-        let verify_ok = neo_spartan_bridge::verify_fold_run(&keypair.vk, run.params(), run.ccs(), &spartan)
-            .map_err(|e| JsValue::from_str(&format!("spartan verify error: {e}")))?;
-        let verify_ms = Date::now() - verify_start;
-
-        let snark = spartan.snark_data.clone();
-        let snark_bytes = snark.len();
-
-        Some(Rv32FibSpartanResult {
-            prove_ms,
-            verify_ms,
-            verify_ok,
-            snark_bytes,
-            snark,
-        })
-    } else {
-        None
-    };
+    let run = rv32::prove_verify_rv32(&program, &config)?;
+    let output = run.outputs[0].expected;
 
     let result = Rv32FibRunResult {
         n,
+        output,
         expected,
-        verify_ok,
-        prove_ms,
-        verify_ms,
-        trace_len,
-        folds,
-        ccs_constraints,
-        ccs_variables,
-        shout_lookups,
-        spartan,
+        output_matches: expected.map(|e| e == output),
+        ram: run.ram,
+        verify_ok: run.verify_ok,
+        prove_ms: run.prove_ms,
+        verify_ms: run.verify_ms,
+        trace_len: run.trace_len,
+        folds: run.folds,
+        ccs_constraints: run.ccs_constraints,
+        ccs_variables: run.ccs_variables,
+        shout_lookups: run.shout_lookups,
+        spartan: run.spartan,
     };
 
-    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    to_js(&result)
+}
+
+/// Config for the Fibonacci I/O shape: `n` at RAM[0x104], and whatever the guest leaves at
+/// RAM[0x100] claimed as the output.
+fn fib_config(n: u32, ram_bytes: u64, chunk_rows: usize) -> Rv32ProveConfig {
+    Rv32ProveConfig {
+        program_base: None,
+        memory_map: None,
+        syscalls: None,
+        xlen: 32,
+        ram_init: vec![Rv32RamInit { addr: 0x104, value: n }],
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        output_regions: vec![Rv32OutputRegion { addr: 0x100, words: 1 }],
+        chunk_rows,
+        ram_bytes: Some(ram_bytes),
+        max_steps: 0,
+        do_spartan: false,
+        includes: HashMap::new(),
+    }
+}
+
+/// Stateful RV32 wrapper, the trace-wiring counterpart of `NeoFoldSession`.
+///
+/// Construct once from a config object (same shape as `prove_verify_rv32_asm`), then:
+/// - `assemble(asm)` to build the ROM image (or `load_elf(bytes)` for a compiled guest)
+/// - `execute()` to run natively and page through the trace with `trace(offset, limit)`
+/// - `prove()` to obtain an opaque `Rv32Proof` handle
+/// - `verify(proof)`, or `verify_statement(proof, statement)` to check a proof against a statement
+/// - `spartan_prove(proof)`, `spartan_verify(proof, spartan)`
+#[wasm_bindgen]
+pub struct Rv32Session {
+    config: Rv32ProveConfig,
+    program: Option<Rv32Program>,
+    trace: Vec<Rv32StepRecord>,
+}
+
+#[wasm_bindgen]
+impl Rv32Session {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue) -> Result<Rv32Session, JsValue> {
+        let config: Rv32ProveConfig = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| NeoDemoError::parse(format!("config error: {e}")))?;
+        config.validate()?;
+        Ok(Rv32Session {
+            config,
+            program: None,
+            trace: Vec::new(),
+        })
+    }
+
+    /// Assemble mini-asm into the session's ROM image and `.data` RAM init; returns the ROM size
+    /// in bytes.
+    pub fn assemble(&mut self, asm: &str) -> Result<usize, JsValue> {
+        let program =
+            riscv_asm::assemble_rv32_mini_asm(asm, &self.config.includes, &self.config.layout().asm_layout())?;
+        if program.rom.is_empty() {
+            return Err(NeoDemoError::assemble("assembled program is empty").into());
+        }
+        let len = program.rom.len();
+        self.program = Some(program);
+        self.trace.clear();
+        Ok(len)
+    }
+
+    /// Load an RV32 ELF executable instead of assembling; returns the entry point. The session's
+    /// `program_base` is replaced by the ELF's.
+    pub fn load_elf(&mut self, elf: &[u8]) -> Result<u32, JsValue> {
+        let image = rv32_elf::load_rv32_elf(elf).map_err(NeoDemoError::parse)?;
+        let mut config = self.config.clone();
+        config.program_base = Some(image.program_base as u64);
+        config.validate_elf()?;
+        self.config = config;
+        self.program = Some(image.program);
+        self.trace.clear();
+        Ok(image.entry)
+    }
+
+    /// Run the assembled program natively (no proving) and return an execution report (see
+    /// `execute_rv32`), with the fold count predicted for the session's `chunk_rows`.
+    pub fn execute(&mut self) -> Result<<Rv32ExecReport as Tsify>::JsType, JsValue> {
+        let (machine, trace) = self.run_native()?;
+        self.trace = trace;
+
+        let report = Rv32ExecReport::new(&machine, &self.trace, self.config.chunk_rows);
+        to_js(&report)
+    }
+
+    /// Number of rows recorded by the last `execute()`.
+    pub fn trace_len(&self) -> usize {
+        self.trace.len()
+    }
+
+    /// Rows `[offset, offset + limit)` of the last `execute()` trace.
+    pub fn trace(&self, offset: usize, limit: usize) -> Result<<Rv32StepRecords as Tsify>::JsType, JsValue> {
+        let start = offset.min(self.trace.len());
+        let end = start.saturating_add(limit).min(self.trace.len());
+        to_js(&Rv32StepRecords(self.trace[start..end].to_vec()))
+    }
+
+    /// Prove the program. The guest is run natively first (failing with the PC and address of
+    /// any fault or out-of-RAM access), and the proof keeps that run so its execution table can
+    /// be inspected with `exec_table(offset, limit)` / `exec_table_csv()`.
+    pub fn prove(&self) -> Result<Rv32Proof, JsValue> {
+        let program = self.program()?;
+        let resolved = self.config.resolve(program)?;
+        let run = rv32::rv32_wiring(program, &resolved.config)?
+            .prove()
+            .map_err(|e| NeoDemoError::prove(format!("prove error: {e}")))?;
+        Ok(Rv32Proof {
+            run,
+            statement: resolved.statement(program),
+            wiring: Rv32WiringInputs::of(program, &resolved.config)?,
+            steps: resolved.steps,
+        })
+    }
+
+    /// Verify `proof`, which must have been produced for the session's current program and layout.
+    pub fn verify(&self, proof: &mut Rv32Proof) -> Result<bool, JsValue> {
+        rv32::check_rv32_program(self.program()?, &self.config, &proof.statement)?;
+        proof
+            .run
+            .verify()
+            .map_err(|e| NeoDemoError::verify(format!("verify error: {e}")))?;
+        Ok(true)
+    }
+
+    /// Check that `proof` attests to `statement`
+    /// (`{ program_base, program_sha256, inputs, outputs, exit_code, journal }`, as returned by
+    /// `proof.statement()`): the initial RAM and output claims are rebuilt from the statement, the
+    /// session's program and the words its config sets outside the statement, must be exactly the
+    /// ones the proof was made from, and the proof is then verified. It only passes if the program
+    /// ran on exactly those public inputs and produced exactly those outputs.
+    pub fn verify_statement(
+        &self,
+        proof: &mut Rv32Proof,
+        statement: <Rv32PublicStatement as Tsify>::JsType,
+    ) -> Result<bool, JsValue> {
+        let statement = parse_statement(statement)?;
+        rv32::check_rv32_statement(self.program()?, &self.config, &statement, &proof.wiring)?;
+        proof
+            .run
+            .verify()
+            .map_err(|e| NeoDemoError::verify(format!("verify error: {e}")))?;
+        Ok(true)
+    }
+
+    /// Compress a proved run into a Spartan2 proof (keys are cached per circuit shape).
+    pub fn spartan_prove(&self, proof: &Rv32Proof) -> Result<SpartanCompressedProof, JsValue> {
+        let compressed = rv32::rv32_spartan_prove(&proof.run)?;
+        Ok(SpartanCompressedProof {
+            inner: compressed.proof,
+            vk: compressed.vk,
+        })
+    }
+
+    /// Verify a Spartan2 compression of `proof`, which must have been produced for the session's
+    /// current program and layout.
+    pub fn spartan_verify(&self, proof: &Rv32Proof, spartan: &SpartanCompressedProof) -> Result<bool, JsValue> {
+        rv32::check_rv32_program(self.program()?, &self.config, &proof.statement)?;
+        neo_spartan_bridge::verify_fold_run(&spartan.vk, proof.run.params(), proof.run.ccs(), &spartan.inner)
+            .map_err(|e| NeoDemoError::spartan_verify(format!("spartan verify error: {e}")).into())
+    }
+}
+
+impl Rv32Session {
+    fn program(&self) -> Result<&Rv32Program, JsValue> {
+        self.program
+            .as_ref()
+            .ok_or_else(|| NeoDemoError::shape("no program: call assemble() first").into())
+    }
+
+    /// Execute the program natively under the session's config.
+    fn run_native(&self) -> Result<(Rv32Machine, Vec<Rv32StepRecord>), JsValue> {
+        let mut machine = self.config.machine(self.program()?)?;
+        let trace = machine.run(self.config.exec_max_steps());
+        Ok((machine, trace))
+    }
+}
+
+/// Opaque proof handle returned by `Rv32Session::prove()`.
+#[wasm_bindgen]
+pub struct Rv32Proof {
+    run: Rv32TraceWiringRun,
+    /// Native execution of the proved program, row-aligned with the execution table.
+    steps: Vec<Rv32StepRecord>,
+    /// Public inputs and output claims the run was proven against.
+    statement: Rv32PublicStatement,
+    /// Initial RAM and output claims the run was built from.
+    wiring: Rv32WiringInputs,
+}
+
+fn parse_statement(statement: <Rv32PublicStatement as Tsify>::JsType) -> Result<Rv32PublicStatement, NeoDemoError> {
+    Rv32PublicStatement::from_js(statement).map_err(|e| NeoDemoError::parse(format!("statement error: {e}")))
+}
+
+#[wasm_bindgen]
+impl Rv32Proof {
+    pub fn prove_ms(&self) -> f64 {
+        self.run.prove_duration().as_secs_f64() * 1000.0
+    }
+
+    pub fn verify_ms(&self) -> Option<f64> {
+        self.run.verify_duration().map(|d| d.as_secs_f64() * 1000.0)
+    }
+
+    pub fn summary(&self) -> Result<<Rv32ProofSummary as Tsify>::JsType, JsValue> {
+        to_js(&Rv32ProofSummary::of(&self.run))
+    }
+
+    /// The public statement this proof attests to:
+    /// `{ program_base, program_sha256, inputs, outputs, exit_code, journal }`.
+    pub fn statement(&self) -> Result<<Rv32PublicStatement as Tsify>::JsType, JsValue> {
+        to_js(&self.statement)
+    }
+
+    /// Number of rows in the prover's execution table.
+    pub fn exec_table_len(&self) -> usize {
+        self.run.exec_table().rows.len()
+    }
+
+    /// Rows `[offset, offset + limit)` of the execution table:
+    /// `[{ row, active, pc, next_pc, word, text, reg_reads, reg_write, mem, shout_lookups }]`.
+    /// `shout_lookups` is counted from the prover's table, the other fields come from the native
+    /// run that preceded proving.
+    pub fn exec_table(&self, offset: usize, limit: usize) -> Result<<Rv32TraceRows as Tsify>::JsType, JsValue> {
+        let rows = rv32_trace::trace_rows(&rv32_trace::shout_lookups(&self.run), &self.steps, offset, limit);
+        to_js(&Rv32TraceRows(rows))
+    }
+
+    /// The whole execution table as CSV, for download.
+    pub fn exec_table_csv(&self) -> String {
+        let rows = rv32_trace::trace_rows(&rv32_trace::shout_lookups(&self.run), &self.steps, 0, usize::MAX);
+        rv32_trace::trace_csv(&rows)
+    }
+}
+
+/// Why `Rv32Debugger::run_until` stopped.
+#[derive(serde::Serialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Rv32StopReason {
+    Breakpoint,
+    Pc,
+    Halted,
+    MaxSteps,
+}
+
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32DebugStop {
+    reason: Rv32StopReason,
+    pc: u32,
+    #[tsify(optional)]
+    line: Option<usize>,
+    /// Instructions executed by this call.
+    executed: usize,
+    /// Instructions executed since the program started.
+    steps: usize,
+    #[tsify(optional)]
+    halt: Option<Rv32HaltReason>,
+}
+
+/// Step-through debugger over the native interpreter, for mini-asm programs (no proving).
+///
+/// Breakpoints are set on 1-based source lines and trigger on the first word the line
+/// assembled to, so a multi-instruction pseudo-op or macro stops once per entry.
+#[wasm_bindgen]
+pub struct Rv32Debugger {
+    machine: Rv32Machine,
+    program_base: u32,
+    /// Source line of each ROM word.
+    lines: Vec<usize>,
+    /// Breakpoint line -> the PCs it stops at.
+    breakpoints: BTreeMap<usize, Vec<u32>>,
+}
+
+#[wasm_bindgen]
+impl Rv32Debugger {
+    /// Assemble `asm` and stop before its first instruction. `ram_init` is an optional
+    /// `[{ addr, value }]` array applied over the program's `.data`, `layout` an optional
+    /// `{ program_base?, memory_map?, syscalls? }` and `includes` an optional `{ name: source }`
+    /// map for `.include`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(asm: &str, ram_init: JsValue, layout: JsValue, includes: JsValue) -> Result<Rv32Debugger, JsValue> {
+        let layout = layout_from_js(layout)?;
+        let (program, machine) = native_machine(asm, &includes_from_js(includes)?, ram_init, &layout)?;
+        Ok(Rv32Debugger {
+            machine,
+            program_base: layout.program_base() as u32,
+            lines: program.lines,
+            breakpoints: BTreeMap::new(),
+        })
+    }
+
+    /// Execute one instruction and return its step record, or `undefined` once halted.
+    pub fn step(&mut self) -> Result<Option<<Rv32StepRecord as Tsify>::JsType>, JsValue> {
+        self.machine.step().map(|record| to_js(&record)).transpose()
+    }
+
+    /// Run until the program halts, reaches `pc`, or hits a breakpoint, executing at least one
+    /// instruction and at most `max_steps` (`0` for the default budget). Returns
+    /// `{ reason: "breakpoint" | "pc" | "halted" | "max_steps", pc, line, executed, steps, halt }`.
+    pub fn run_until(
+        &mut self,
+        pc: Option<u32>,
+        max_steps: usize,
+    ) -> Result<<Rv32DebugStop as Tsify>::JsType, JsValue> {
+        to_js(&self.run_to_stop(pc, max_steps))
+    }
+
+    /// Set a breakpoint on source `line`; returns the number of places it stops at.
+    pub fn set_breakpoint(&mut self, line: usize) -> Result<usize, JsValue> {
+        Ok(self.add_breakpoint(line)?)
+    }
+
+    pub fn clear_breakpoint(&mut self, line: usize) -> bool {
+        self.breakpoints.remove(&line).is_some()
+    }
+
+    /// Lines with a breakpoint, ascending.
+    pub fn breakpoints(&self) -> Vec<usize> {
+        self.breakpoints.keys().copied().collect()
+    }
+
+    pub fn pc(&self) -> u32 {
+        self.machine.pc
+    }
+
+    /// Source line of the next instruction, if `pc` is inside the program (and not in the
+    /// syscall shim).
+    pub fn line(&self) -> Option<usize> {
+        let off = self.machine.pc.checked_sub(self.program_base)?;
+        if off & 3 != 0 {
+            return None;
+        }
+        self.lines.get(off as usize / 4).copied().filter(|&line| line > 0)
+    }
+
+    pub fn steps(&self) -> usize {
+        self.machine.steps
+    }
+
+    /// `x0..x31`.
+    pub fn registers(&self) -> Vec<u32> {
+        self.machine.regs.to_vec()
+    }
+
+    /// `len` bytes of RAM starting at `addr` (unwritten bytes read as 0), at most 1 MiB.
+    pub fn read_mem(&self, addr: u32, len: u32) -> Result<Vec<u8>, JsValue> {
+        if len > 1 << 20 {
+            return Err(NeoDemoError::parse(format!("read_mem error: {len} bytes exceeds the 1 MiB limit")).into());
+        }
+        Ok(self.machine.read_bytes(addr, len))
+    }
+}
+
+impl Rv32Debugger {
+    fn run_to_stop(&mut self, pc: Option<u32>, max_steps: usize) -> Rv32DebugStop {
+        let budget = if max_steps > 0 { max_steps } else { DEFAULT_EXEC_MAX_STEPS };
+        let mut executed = 0;
+        let reason = loop {
+            if self.machine.halted.is_some() {
+                break Rv32StopReason::Halted;
+            }
+            if executed > 0 {
+                if Some(self.machine.pc) == pc {
+                    break Rv32StopReason::Pc;
+                }
+                if self.breakpoints.values().any(|pcs| pcs.contains(&self.machine.pc)) {
+                    break Rv32StopReason::Breakpoint;
+                }
+            }
+            if executed >= budget {
+                break Rv32StopReason::MaxSteps;
+            }
+            self.machine.step();
+            executed += 1;
+        };
+
+        Rv32DebugStop {
+            reason,
+            pc: self.machine.pc,
+            line: self.line(),
+            executed,
+            steps: self.machine.steps,
+            halt: self.machine.halted.clone(),
+        }
+    }
+
+    fn add_breakpoint(&mut self, line: usize) -> Result<usize, NeoDemoError> {
+        let pcs: Vec<u32> = (0..self.lines.len())
+            .filter(|&i| line > 0 && self.lines[i] == line && (i == 0 || self.lines[i - 1] != line))
+            .map(|i| self.program_base.wrapping_add(4 * i as u32))
+            .collect();
+        if pcs.is_empty() {
+            return Err(NeoDemoError::parse(format!("breakpoint error: no code on line {line}")));
+        }
+        let count = pcs.len();
+        self.breakpoints.insert(line, pcs);
+        Ok(count)
+    }
 }
 
 /// Stateful JS-facing session wrapper.
@@ -179,6 +745,10 @@ pub fn prove_verify_rv32_trace_fibonacci_asm(
 #[wasm_bindgen]
 pub struct NeoFoldSession {
     inner: TestExportSession,
+    /// Binds serialized proofs to the circuit they were produced for, so `verify` can reject a
+    /// proof imported into a session built from a different circuit with a clear error.
+    circuit_digest: [u8; 32],
+    spartan_keys: Option<SpartanKeys>,
 }
 
 fn fold_run_witness_placeholder(run: &neo_fold::shard::ShardProof) -> FoldRunWitness {
@@ -196,49 +766,52 @@ impl NeoFoldSession {
     /// Note: serde ignores unknown fields, so passing a full `TestExport` JSON is also accepted.
     #[wasm_bindgen(constructor)]
     pub fn new(circuit_json: &str) -> Result<NeoFoldSession, JsValue> {
-        let inner = TestExportSession::new_from_circuit_json(circuit_json)
-            .map_err(|e| JsValue::from_str(&e))?;
-        Ok(NeoFoldSession { inner })
+        let inner = TestExportSession::new_from_circuit_json(circuit_json).map_err(NeoDemoError::parse)?;
+        let circuit_digest = circuit_digest(inner.params(), inner.ccs())?;
+        Ok(NeoFoldSession {
+            inner,
+            circuit_digest,
+            spartan_keys: None,
+        })
     }
 
     pub fn step_count(&self) -> usize {
         self.inner.step_count()
     }
 
-    pub fn setup_timings_ms(&self) -> Result<JsValue, JsValue> {
-        serde_wasm_bindgen::to_value(self.inner.setup_timings_ms())
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn setup_timings_ms(&self) -> Result<<SetupTimingsMs as Tsify>::JsType, JsValue> {
+        mirror::<SetupTimingsMs>("setup timings", self.inner.setup_timings_ms())
     }
 
-    pub fn params_summary(&self) -> Result<JsValue, JsValue> {
-        serde_wasm_bindgen::to_value(&self.inner.params_summary())
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn params_summary(&self) -> Result<<ParamsSummary as Tsify>::JsType, JsValue> {
+        mirror::<ParamsSummary>("params summary", &self.inner.params_summary())
     }
 
-    pub fn circuit_summary(&self) -> Result<JsValue, JsValue> {
-        serde_wasm_bindgen::to_value(&self.inner.circuit_summary())
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn circuit_summary(&self) -> Result<<CircuitSummary as Tsify>::JsType, JsValue> {
+        mirror::<CircuitSummary>("circuit summary", &self.inner.circuit_summary())
     }
 
     /// Add one step from a witness vector `z` encoded as JSON array of u64s.
     pub fn add_step_witness_json(&mut self, witness_json: &str) -> Result<(), JsValue> {
+        let step = self.inner.step_count();
         self.inner
             .add_step_witness_json(witness_json)
-            .map_err(|e| JsValue::from_str(&e))
+            .map_err(|e| NeoDemoError::shape(e).at_step(step).into())
     }
 
     /// Add one step from `(x, w)` encoded as JSON arrays of u64s.
     pub fn add_step_io_json(&mut self, x_json: &str, w_json: &str) -> Result<(), JsValue> {
+        let step = self.inner.step_count();
         self.inner
             .add_step_io_json(x_json, w_json)
-            .map_err(|e| JsValue::from_str(&e))
+            .map_err(|e| NeoDemoError::shape(e).at_step(step).into())
     }
 
     /// Add all witness steps from a full `TestExport` JSON (uses only `witness`).
     pub fn add_steps_from_test_export_json(&mut self, json: &str) -> Result<(), JsValue> {
         self.inner
             .add_steps_from_test_export_json(json)
-            .map_err(|e| JsValue::from_str(&e))
+            .map_err(|e| NeoDemoError::shape(e).into())
     }
 
     /// Set verifier-side step-linking equality pairs from JSON.
@@ -247,23 +820,64 @@ impl NeoFoldSession {
     pub fn set_step_linking_pairs_json(&mut self, json: &str) -> Result<(), JsValue> {
         self.inner
             .set_step_linking_pairs_json(json)
-            .map_err(|e| JsValue::from_str(&e))
+            .map_err(|e| NeoDemoError::parse(e).into())
     }
 
     pub fn fold_and_prove(&mut self) -> Result<NeoFoldProof, JsValue> {
         let (proof, fold_step_ms) = self
             .inner
             .fold_and_prove_with_step_timings()
-            .map_err(|e| JsValue::from_str(&e))?;
-        Ok(NeoFoldProof { proof, fold_step_ms })
+            .map_err(NeoDemoError::prove)?;
+        Ok(NeoFoldProof {
+            proof,
+            fold_step_ms,
+            circuit_digest: self.circuit_digest,
+        })
     }
 
     pub fn verify(&self, proof: &NeoFoldProof) -> Result<bool, JsValue> {
-        self.inner.verify(&proof.proof).map_err(|e| JsValue::from_str(&e))
+        if proof.circuit_digest != self.circuit_digest {
+            return Err(NeoDemoError::shape(
+                "verify error: proof was produced for a different circuit (circuit digest mismatch)",
+            )
+            .into());
+        }
+        self.inner
+            .verify(&proof.proof)
+            .map_err(|e| NeoDemoError::verify(e).into())
+    }
+
+    /// Run (or reuse) the Spartan setup for proofs shaped like `proof`.
+    ///
+    /// Keys are memoized per (params, CCS, step count); returns the setup time in ms, or `0` when
+    /// cached keys were reused. Calling this is optional: `spartan_prove` sets up on demand.
+    pub fn spartan_setup(&mut self, proof: &NeoFoldProof) -> Result<f64, JsValue> {
+        let (keys, cached) = self.spartan_keys_for(proof)?;
+        Ok(if cached { 0.0 } else { keys.setup_ms })
+    }
+
+    /// Export the cached Spartan keypair (pk + vk) for reuse in a later session.
+    pub fn spartan_keys_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        let keys = self
+            .spartan_keys
+            .as_ref()
+            .ok_or_else(|| NeoDemoError::parse("no spartan keys: call spartan_setup() or spartan_prove() first"))?;
+        Ok(keys.to_bytes()?)
+    }
+
+    /// Import a keypair from `spartan_keys_bytes()` for proofs with `step_count` folding steps.
+    pub fn import_spartan_keys(&mut self, bytes: &[u8], step_count: usize) -> Result<(), JsValue> {
+        let id = spartan_keys_id(&self.circuit_digest, step_count);
+        let keys = SpartanKeys::from_bytes(bytes, id)?;
+        self.spartan_keys = Some(keys);
+        Ok(())
     }
 
     /// Compress a folding proof into a Spartan2 proof (Merkle-MLE engine).
-    pub fn spartan_prove(&self, proof: &NeoFoldProof) -> Result<SpartanCompressedProof, JsValue> {
+    pub fn spartan_prove(&mut self, proof: &NeoFoldProof) -> Result<SpartanCompressedProof, JsValue> {
+        self.spartan_keys_for(proof)?;
+        let keys = self.spartan_keys.as_ref().expect("spartan keys populated above");
+
         let acc_init = self
             .inner
             .initial_accumulator()
@@ -271,27 +885,43 @@ impl NeoFoldSession {
             .unwrap_or(&[]);
 
         let witness = fold_run_witness_placeholder(&proof.proof);
-        let keypair = neo_spartan_bridge::setup_fold_run(self.inner.params(), self.inner.ccs(), acc_init, &proof.proof, witness.clone())
-            .map_err(|e| JsValue::from_str(&format!("spartan setup error: {e}")))?;
         let spartan = neo_spartan_bridge::prove_fold_run(
-            &keypair.pk,
+            &keys.pk,
             self.inner.params(),
             self.inner.ccs(),
             acc_init,
             &proof.proof,
             witness,
         )
-        .map_err(|e| JsValue::from_str(&format!("spartan prove error: {e}")))?;
+        .map_err(|e| NeoDemoError::spartan_prove(format!("spartan prove error: {e}")))?;
 
         Ok(SpartanCompressedProof {
             inner: spartan,
-            vk: keypair.vk,
+            vk: keys.vk.clone(),
         })
     }
 
     pub fn spartan_verify(&self, proof: &SpartanCompressedProof) -> Result<bool, JsValue> {
         neo_spartan_bridge::verify_fold_run(&proof.vk, self.inner.params(), self.inner.ccs(), &proof.inner)
-            .map_err(|e| JsValue::from_str(&format!("spartan verify error: {e}")))
+            .map_err(|e| NeoDemoError::spartan_verify(format!("spartan verify error: {e}")).into())
+    }
+}
+
+impl NeoFoldSession {
+    fn spartan_keys_for(&mut self, proof: &NeoFoldProof) -> Result<(&SpartanKeys, bool), JsValue> {
+        let id = spartan_keys_id(&self.circuit_digest, proof.proof.steps.len());
+        let acc_init = self
+            .inner
+            .initial_accumulator()
+            .map(|acc| acc.me.as_slice())
+            .unwrap_or(&[]);
+        let (params, ccs) = (self.inner.params(), self.inner.ccs());
+        SpartanKeys::get_or_setup(&mut self.spartan_keys, id, || {
+            let witness = fold_run_witness_placeholder(&proof.proof);
+            neo_spartan_bridge::setup_fold_run(params, ccs, acc_init, &proof.proof, witness)
+                .map(|kp| (kp.pk, kp.vk))
+                .map_err(|e| NeoDemoError::spartan_setup(format!("spartan setup error: {e}")).into())
+        })
     }
 }
 
@@ -300,29 +930,102 @@ impl NeoFoldSession {
 pub struct NeoFoldProof {
     proof: neo_fold::shard::ShardProof,
     fold_step_ms: Vec<f64>,
+    circuit_digest: [u8; 32],
+}
+
+/// Leading bytes of every serialized `NeoFoldProof`.
+const PROOF_MAGIC: [u8; 8] = *b"NEOFOLD\0";
+
+/// Bump whenever the envelope layout or the `ShardProof` encoding changes incompatibly.
+const PROOF_FORMAT_VERSION: u32 = 1;
+
+/// Fixed-size prefix of the envelope, decoded first so a foreign file or an old/new format
+/// version is reported as such instead of as an opaque bincode error.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ProofEnvelopeHeader {
+    magic: [u8; 8],
+    version: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ProofEnvelope {
+    header: ProofEnvelopeHeader,
+    circuit_digest: [u8; 32],
+    fold_step_ms: Vec<f64>,
+    proof: neo_fold::shard::ShardProof,
+}
+
+/// Check the envelope header of `bytes` (a `what` proof) before decoding the rest.
+fn read_envelope_header(bytes: &[u8], magic: [u8; 8], what: &str) -> Result<(), NeoDemoError> {
+    let header: ProofEnvelopeHeader = bincode::deserialize(bytes)
+        .map_err(|e| NeoDemoError::parse(format!("deserialize error: truncated proof header: {e}")))?;
+    if header.magic != magic {
+        return Err(NeoDemoError::parse(format!("deserialize error: not {what} proof (bad magic)")));
+    }
+    if header.version != PROOF_FORMAT_VERSION {
+        return Err(NeoDemoError::parse(format!(
+            "deserialize error: unsupported proof format version {} (expected {PROOF_FORMAT_VERSION})",
+            header.version
+        )));
+    }
+    Ok(())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
 }
 
 #[wasm_bindgen]
 impl NeoFoldProof {
+    /// Serialize into a portable, versioned byte envelope:
+    /// `magic || format version || circuit digest || fold step timings || proof`.
+    ///
+    /// The bytes can be stored or sent elsewhere and loaded back with `NeoFoldProof.from_bytes()`,
+    /// then checked with `NeoFoldSession::verify` on a session built from the same circuit.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        let envelope = ProofEnvelope {
+            header: ProofEnvelopeHeader {
+                magic: PROOF_MAGIC,
+                version: PROOF_FORMAT_VERSION,
+            },
+            circuit_digest: self.circuit_digest,
+            fold_step_ms: self.fold_step_ms.clone(),
+            proof: self.proof.clone(),
+        };
+        bincode::serialize(&envelope).map_err(|e| NeoDemoError::serialize(format!("serialize error: {e}")).into())
+    }
+
+    /// Load a proof previously produced by `to_bytes()`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<NeoFoldProof, JsValue> {
+        read_envelope_header(bytes, PROOF_MAGIC, "a neo-fold")?;
+        let envelope: ProofEnvelope =
+            bincode::deserialize(bytes).map_err(|e| NeoDemoError::parse(format!("deserialize error: {e}")))?;
+        Ok(NeoFoldProof {
+            proof: envelope.proof,
+            fold_step_ms: envelope.fold_step_ms,
+            circuit_digest: envelope.circuit_digest,
+        })
+    }
+
+    /// Hex-encoded digest of the circuit (params + CCS) this proof was produced for.
+    pub fn circuit_digest_hex(&self) -> String {
+        hex_encode(&self.circuit_digest)
+    }
+
     pub fn step_count(&self) -> usize {
         self.proof.steps.len()
     }
 
-    pub fn fold_step_ms(&self) -> Result<JsValue, JsValue> {
-        serde_wasm_bindgen::to_value(&self.fold_step_ms)
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn fold_step_ms(&self) -> Result<<FoldStepMs as Tsify>::JsType, JsValue> {
+        to_js(&FoldStepMs(self.fold_step_ms.clone()))
     }
 
-    pub fn proof_estimate(&self) -> Result<JsValue, JsValue> {
-        let est = estimate_proof(&self.proof);
-        serde_wasm_bindgen::to_value(&est)
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn proof_estimate(&self) -> Result<<ProofEstimate as Tsify>::JsType, JsValue> {
+        mirror::<ProofEstimate>("proof estimate", &estimate_proof(&self.proof))
     }
 
-    pub fn folding_summary(&self) -> Result<JsValue, JsValue> {
-        let summary = folding_summary(&self.proof);
-        serde_wasm_bindgen::to_value(&summary)
-            .map_err(|e| JsValue::from_str(&format!("serialize error: {e}")))
+    pub fn folding_summary(&self) -> Result<<FoldingSummary as Tsify>::JsType, JsValue> {
+        mirror::<FoldingSummary>("folding summary", &folding_summary(&self.proof))
     }
 }
 
@@ -345,6 +1048,11 @@ impl SpartanCompressedProof {
         self.inner.snark_data.clone()
     }
 
+    /// Serialized verifier key (bincode), for use with `verify_spartan_bytes`.
+    pub fn vk_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        bincode::serialize(&self.vk).map_err(|e| NeoDemoError::serialize(format!("serialize error: {e}")).into())
+    }
+
     /// Size of the combined artifact (vk + snark).
     ///
     /// This is optional in the UI; when present it can be used to estimate vk size as
@@ -352,7 +1060,150 @@ impl SpartanCompressedProof {
     pub fn vk_and_snark_bytes_len(&self) -> usize {
         // NOTE: neo-spartan-bridge no longer carries vk bytes in the proof object; keep this
         // helper for the demo UI by counting the serialized vk plus the snark bytes.
-        let vk_len = bincode::serialize(&self.vk).map(|b| b.len()).unwrap_or(0);
+        let vk_len = self.vk_bytes().map(|b| b.len()).unwrap_or(0);
         vk_len + self.inner.snark_data.len()
     }
 }
+
+/// Verify a downloaded Spartan proof without re-proving.
+///
+/// `vk_bytes` comes from `SpartanCompressedProof::vk_bytes()`, `snark_bytes` from
+/// `SpartanCompressedProof::bytes()`, and `circuit_json` is the circuit the folding proof was
+/// produced for (same schema as `NeoFoldSession`); params and CCS are rebuilt from it.
+#[wasm_bindgen]
+pub fn verify_spartan_bytes(vk_bytes: &[u8], snark_bytes: &[u8], circuit_json: &str) -> Result<bool, JsValue> {
+    Ok(verify_spartan_artifact(vk_bytes, snark_bytes, circuit_json)?)
+}
+
+fn verify_spartan_artifact(vk_bytes: &[u8], snark_bytes: &[u8], circuit_json: &str) -> Result<bool, NeoDemoError> {
+    if snark_bytes.is_empty() {
+        return Err(NeoDemoError::parse("spartan verify error: snark bytes are empty"));
+    }
+    let vk: neo_spartan_bridge::SpartanVerifierKey = bincode::deserialize(vk_bytes)
+        .map_err(|e| NeoDemoError::parse(format!("deserialize error: vk: {e}")))?;
+    let circuit = TestExportSession::new_from_circuit_json(circuit_json).map_err(NeoDemoError::parse)?;
+    let spartan = neo_spartan_bridge::api::SpartanProof {
+        snark_data: snark_bytes.to_vec(),
+    };
+    neo_spartan_bridge::verify_fold_run(&vk, circuit.params(), circuit.ccs(), &spartan)
+        .map_err(|e| NeoDemoError::spartan_verify(format!("spartan verify error: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NeoDemoErrorKind;
+
+    fn header(magic: [u8; 8], version: u32) -> Vec<u8> {
+        bincode::serialize(&ProofEnvelopeHeader { magic, version }).unwrap()
+    }
+
+    fn header_error(bytes: &[u8]) -> NeoDemoError {
+        let err = read_envelope_header(bytes, PROOF_MAGIC, "a neo-fold").expect_err("header should be rejected");
+        assert_eq!(err.kind, NeoDemoErrorKind::Parse, "{}", err.message);
+        err
+    }
+
+    #[test]
+    fn proof_envelopes_reject_foreign_and_other_version_bytes() {
+        read_envelope_header(&header(PROOF_MAGIC, PROOF_FORMAT_VERSION), PROOF_MAGIC, "a neo-fold").unwrap();
+
+        let err = header_error(&header(PROOF_MAGIC, PROOF_FORMAT_VERSION + 1));
+        assert!(err.message.contains("unsupported proof format version 2 (expected 1)"), "{}", err.message);
+        let err = header_error(&header(*b"NEOSPRT\0", PROOF_FORMAT_VERSION));
+        assert!(err.message.contains("not a neo-fold proof (bad magic)"), "{}", err.message);
+        let err = header_error(&PROOF_MAGIC[..5]);
+        assert!(err.message.contains("truncated proof header"), "{}", err.message);
+    }
+
+    fn debugger(src: &str, program_base: u32) -> Rv32Debugger {
+        let layout = Rv32AsmLayout {
+            text_base: program_base,
+            ..Rv32AsmLayout::default()
+        };
+        let out = riscv_asm::assemble_rv32(src, &HashMap::new(), &layout);
+        let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        Rv32Debugger {
+            machine: Rv32Machine::new(program_base, &program.rom, &program.ram_init).unwrap(),
+            program_base,
+            lines: program.lines,
+            breakpoints: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn debugger_stops_at_line_breakpoints() {
+        let base = 0x8000_0000;
+        let src = "\
+    li t0, 0
+    li t1, 3
+loop:
+    addi t0, t0, 1
+    blt t0, t1, loop
+    li a0, 0x12345
+    ecall
+";
+        let mut dbg = debugger(src, base);
+        assert_eq!(dbg.add_breakpoint(4).unwrap(), 1);
+        // A multi-word pseudo-instruction stops once, on its first word.
+        assert_eq!(dbg.add_breakpoint(6).unwrap(), 1);
+        assert_eq!(dbg.add_breakpoint(3).unwrap_err().kind, NeoDemoErrorKind::Parse);
+        assert_eq!(dbg.breakpoints(), [4, 6]);
+
+        let stop = dbg.run_to_stop(None, 0);
+        assert!(matches!(stop.reason, Rv32StopReason::Breakpoint));
+        assert_eq!((stop.pc, stop.line, stop.executed, stop.steps), (base + 8, Some(4), 2, 2));
+        // Resuming on a breakpoint runs at least one instruction before stopping again.
+        let stop = dbg.run_to_stop(None, 0);
+        assert_eq!((stop.line, stop.executed), (Some(4), 2));
+        assert_eq!(dbg.registers()[5], 1);
+
+        assert!(dbg.clear_breakpoint(4));
+        assert!(!dbg.clear_breakpoint(4));
+        let stop = dbg.run_to_stop(None, 0);
+        assert_eq!((stop.pc, stop.line, stop.steps), (base + 16, Some(6), 8));
+        let stop = dbg.run_to_stop(Some(base + 24), 0);
+        assert!(matches!(stop.reason, Rv32StopReason::Pc));
+        assert_eq!((stop.line, dbg.registers()[10]), (Some(7), 0x12345));
+        let stop = dbg.run_to_stop(None, 0);
+        assert!(matches!(stop.reason, Rv32StopReason::Halted));
+        assert_eq!(stop.halt, Some(Rv32HaltReason::Ecall));
+
+        let mut dbg = debugger("spin:\n    j spin\n", 0);
+        let stop = dbg.run_to_stop(None, 5);
+        assert!(matches!(stop.reason, Rv32StopReason::MaxSteps));
+        assert_eq!(stop.executed, 5);
+    }
+
+    /// The output the Fibonacci entry point claims, resolved from a native run of `src`.
+    fn fib_output(src: &str, n: u32) -> Result<u32, NeoDemoError> {
+        let out = riscv_asm::assemble_rv32(src, &HashMap::new(), &Rv32AsmLayout::default());
+        let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        let config = fib_config(n, 0x800, 4);
+        config.validate()?;
+        let resolved = config.resolve(&program)?;
+        assert_eq!(resolved.config.outputs.len(), 1);
+        assert_eq!(resolved.config.outputs[0].addr, 0x100);
+        Ok(resolved.config.outputs[0].expected)
+    }
+
+    #[test]
+    fn fibonacci_entry_point_claims_what_the_guest_computed() {
+        let fib = include_str!("../../web/examples/rv32_fibonacci.asm");
+        assert_eq!(fib_output(fib, 10).unwrap(), 55);
+        // Any guest with the same I/O shape works, including a wrong one: the claim is its output.
+        let double = "    lw a0, 0x104(x0)\n    add a0, a0, a0\n    sw a0, 0x100(x0)\n    ecall\n";
+        assert_eq!(fib_output(double, 21).unwrap(), 42);
+
+        let err = fib_output("spin:\n    j spin\n", 3).expect_err("guest that never halts");
+        assert_eq!(err.kind, NeoDemoErrorKind::Prove);
+        assert!(err.message.contains("did not halt via ecall"), "{}", err.message);
+    }
+
+    #[test]
+    fn spartan_artifacts_without_a_snark_are_rejected_up_front() {
+        let err = verify_spartan_artifact(&[], &[], "{}").expect_err("empty snark");
+        assert_eq!(err.kind, NeoDemoErrorKind::Parse);
+        assert!(err.message.contains("snark bytes are empty"), "{}", err.message);
+    }
+}
@@ -1,12 +1,105 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
 
-use deprecated_neo_memory::riscv::lookups::{encode_program, BranchCondition, RiscvInstruction, RiscvMemOp, RiscvOpcode};
+use tsify::Tsify;
+
+use crate::error::NeoDemoError;
+
+use deprecated_neo_memory::riscv::lookups::{
+    encode_program, BranchCondition, RiscvInstruction, RiscvMemOp, RiscvOpcode,
+};
 
 #[derive(Debug)]
 enum PendingInstr {
     Resolved(RiscvInstruction),
-    Beq { rs1: u8, rs2: u8, target: String },
-    Jal { rd: u8, target: String },
+    /// Fixed multi-instruction expansion (e.g. `li` with a 32-bit immediate).
+    Expanded(Vec<RiscvInstruction>),
+    /// Instructions whose immediates are patched from symbol addresses once the layout is known.
+    Reloc(Vec<RelocWord>),
+    /// `nop` padding up to the next boundary of this many bytes (`.align` in `.text`). Its size
+    /// depends on everything before it, so `Layout` recomputes it with every relaxation pass.
+    Align(u32),
+}
+
+/// How a symbol address is folded into an instruction's immediate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelocKind {
+    /// B-type offset `sym - pc` (branches).
+    Branch,
+    /// J-type offset `sym - pc` (`jal`).
+    Jal,
+    /// U-type `%hi(sym)`: upper 20 bits of the absolute address.
+    Hi20,
+    /// I/S-type `%lo(sym)`: low 12 bits of the absolute address.
+    Lo12,
+    /// U-type `%pcrel_hi(sym)`: upper 20 bits of `sym - pc`.
+    PcrelHi20,
+    /// I/S-type `%pcrel_lo(label)`: low 12 bits of the offset computed by the `%pcrel_hi` on the
+    /// `auipc` at `label`.
+    PcrelLo12,
+    /// Low half of a pseudo-instruction's own `auipc` pair (`la`, `call`): low 12 bits of
+    /// `sym - pc` taken at the preceding `auipc`.
+    PcrelLo12Paired,
+}
+
+#[derive(Debug, Clone)]
+struct Reloc {
+    kind: RelocKind,
+    symbol: String,
+    addend: i32,
+}
+
+#[derive(Debug)]
+struct RelocWord {
+    instr: RiscvInstruction,
+    reloc: Reloc,
+}
+
+/// An immediate operand: a value known now, or a relocation resolved after layout.
+enum Imm {
+    Value(i32),
+    Reloc(Reloc),
+}
+
+/// `instr` with its immediate field replaced by `imm`.
+fn with_imm(instr: &RiscvInstruction, imm: i32) -> RiscvInstruction {
+    let mut instr = instr.clone();
+    match &mut instr {
+        RiscvInstruction::IAlu { imm: slot, .. }
+        | RiscvInstruction::Load { imm: slot, .. }
+        | RiscvInstruction::Store { imm: slot, .. }
+        | RiscvInstruction::Branch { imm: slot, .. }
+        | RiscvInstruction::Jal { imm: slot, .. }
+        | RiscvInstruction::Jalr { imm: slot, .. }
+        | RiscvInstruction::Lui { imm: slot, .. }
+        | RiscvInstruction::Auipc { imm: slot, .. } => *slot = imm,
+        _ => {}
+    }
+    instr
+}
+
+/// `instr` with `imm` applied now, or as a single relocated word.
+fn imm_instr(instr: RiscvInstruction, imm: Imm) -> PendingInstr {
+    match imm {
+        Imm::Value(value) => PendingInstr::Resolved(with_imm(&instr, value)),
+        Imm::Reloc(reloc) => PendingInstr::Reloc(vec![RelocWord { instr, reloc }]),
+    }
+}
+
+impl PendingInstr {
+    /// Number of 32-bit words this item assembles to when placed at `addr`.
+    fn size_words(&self, addr: u32) -> usize {
+        match self {
+            PendingInstr::Expanded(seq) => seq.len(),
+            PendingInstr::Reloc(words) => words.len(),
+            PendingInstr::Resolved(_) => 1,
+            PendingInstr::Align(bytes) => {
+                let addr = addr as u64;
+                (addr.next_multiple_of(*bytes as u64) - addr) as usize / 4
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -15,6 +108,138 @@ struct PendingLine {
     instr: PendingInstr,
 }
 
+/// Per-line parsing context: the line number for messages and the `.equ` constants defined so far.
+struct LineCx<'a> {
+    line_no: usize,
+    consts: &'a HashMap<String, i64>,
+}
+
+/// Error raised while assembling one line. `token` is the offending source text, used to
+/// narrow the diagnostic span to it.
+#[derive(Debug)]
+struct AsmError {
+    line_no: usize,
+    code: &'static str,
+    message: String,
+    token: Option<String>,
+    hint: Option<String>,
+}
+
+impl AsmError {
+    fn new(line_no: usize, code: &'static str, message: impl Into<String>) -> Self {
+        AsmError {
+            line_no,
+            code,
+            message: message.into(),
+            token: None,
+            hint: None,
+        }
+    }
+
+    fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.trim().trim_end_matches(',').to_string());
+        self
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Name the macro expansion or include file the failing statement came from.
+    fn origin(mut self, origin: Option<&str>) -> Self {
+        if let Some(origin) = origin {
+            self.message = format!("{} (in {origin})", self.message);
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, Tsify)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Assembler diagnostic positioned for an editor: `line` and columns are 1-based, columns count
+/// characters and `column_end` is exclusive.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct AsmDiagnostic {
+    pub line: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    #[tsify(optional)]
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for AsmDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, " ({hint})")?;
+        }
+        Ok(())
+    }
+}
+
+impl AsmDiagnostic {
+    /// Position `err` on its source line: the label if the token names it, else the first
+    /// occurrence of the token after any label, else the whole statement.
+    fn locate(err: AsmError, severity: Severity, source_lines: &[&str]) -> Self {
+        let line = source_lines.get(err.line_no.wrapping_sub(1)).copied().unwrap_or("");
+        let code = strip_comment(line).trim_end().to_ascii_lowercase();
+        let colon = code.find(':');
+        let body_start = colon.map(|i| i + 1).unwrap_or(0);
+        let stmt_start = body_start + (code[body_start..].len() - code[body_start..].trim_start().len());
+
+        let span = err
+            .token
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .filter(|t| !t.is_empty())
+            .and_then(|t| {
+                let label = colon.map(|c| code[..c].trim()).filter(|l| *l == t);
+                let at = match label {
+                    Some(_) => code.find(&t)?,
+                    None => code[body_start..]
+                        .find(&t)
+                        .map(|i| i + body_start)
+                        .or_else(|| code.find(&t))?,
+                };
+                Some((at, at + t.len()))
+            })
+            .unwrap_or((stmt_start.min(code.len()), code.len()));
+        let column = |byte: usize| code[..byte].chars().count() + 1;
+
+        AsmDiagnostic {
+            line: err.line_no,
+            column_start: column(span.0),
+            column_end: column(span.1).max(column(span.0) + 1),
+            severity,
+            code: err.code,
+            message: err.message,
+            hint: err.hint,
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            cur.push((prev[j] + (ca != cb) as usize).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 fn strip_comment(line: &str) -> &str {
     let hash = line.find('#');
     let slash = line.find("//");
@@ -26,17 +251,23 @@ fn strip_comment(line: &str) -> &str {
     }
 }
 
-fn parse_reg(token: &str, line_no: usize) -> Result<u8, String> {
+fn parse_reg(token: &str, line_no: usize) -> Result<u8, AsmError> {
     let t = token.trim().trim_end_matches(',').to_ascii_lowercase();
     if t.is_empty() {
-        return Err(format!("line {line_no}: expected register, got empty token"));
+        return Err(AsmError::new(
+            line_no,
+            "bad-register",
+            "expected register, got empty token",
+        ));
     }
     if let Some(num) = t.strip_prefix('x') {
         let idx: u8 = num
             .parse()
-            .map_err(|_| format!("line {line_no}: invalid register '{token}'"))?;
+            .map_err(|_| AsmError::new(line_no, "bad-register", format!("invalid register '{token}'")).token(token))?;
         if idx > 31 {
-            return Err(format!("line {line_no}: invalid register '{token}' (x0..x31)"));
+            return Err(
+                AsmError::new(line_no, "bad-register", format!("invalid register '{token}' (x0..x31)")).token(token),
+            );
         }
         return Ok(idx);
     }
@@ -75,33 +306,419 @@ fn parse_reg(token: &str, line_no: usize) -> Result<u8, String> {
         "t5" => 30,
         "t6" => 31,
         _ => {
-            return Err(format!(
-                "line {line_no}: unknown register '{token}' (expected x0..x31 or ABI names like a0/t0)"
-            ))
+            return Err(
+                AsmError::new(line_no, "bad-register", format!("unknown register '{token}'"))
+                    .token(token)
+                    .hint("expected x0..x31 or ABI names like a0/t0"),
+            )
         }
     };
     Ok(reg)
 }
 
-fn parse_i32(token: &str, line_no: usize) -> Result<i32, String> {
+fn parse_i64(token: &str, cx: &LineCx) -> Result<i64, AsmError> {
+    let line_no = cx.line_no;
+    if let Some(&value) = cx.consts.get(token.trim().trim_end_matches(',')) {
+        return Ok(value);
+    }
     let s0 = token.trim().trim_end_matches(',').replace('_', "");
     if s0.is_empty() {
-        return Err(format!("line {line_no}: expected immediate, got empty token"));
+        return Err(AsmError::new(
+            line_no,
+            "bad-immediate",
+            "expected immediate, got empty token",
+        ));
     }
-    let (neg, s) = s0.strip_prefix('-').map(|rest| (true, rest)).unwrap_or((false, s0.as_str()));
+    let (neg, s) = s0
+        .strip_prefix('-')
+        .map(|rest| (true, rest))
+        .unwrap_or((false, s0.as_str()));
     let val: i64 = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
-        i64::from_str_radix(hex, 16).map_err(|_| format!("line {line_no}: invalid hex immediate '{token}'"))?
+        i64::from_str_radix(hex, 16).map_err(|_| {
+            AsmError::new(line_no, "bad-immediate", format!("invalid hex immediate '{token}'")).token(token)
+        })?
     } else {
         s.parse::<i64>()
-            .map_err(|_| format!("line {line_no}: invalid immediate '{token}'"))?
+            .map_err(|_| AsmError::new(line_no, "bad-immediate", format!("invalid immediate '{token}'")).token(token))?
     };
-    let val = if neg { -val } else { val };
+    Ok(if neg { -val } else { val })
+}
+
+fn parse_i32(token: &str, cx: &LineCx) -> Result<i32, AsmError> {
+    let line_no = cx.line_no;
+    let val = parse_i64(token, cx)?;
     if val < i32::MIN as i64 || val > i32::MAX as i64 {
-        return Err(format!("line {line_no}: immediate out of range for i32: '{token}'"));
+        return Err(AsmError::new(
+            line_no,
+            "imm-range",
+            format!("immediate out of range for i32: '{token}'"),
+        )
+        .token(token));
     }
     Ok(val as i32)
 }
 
+/// Any 32-bit value, signed or unsigned (`li` accepts both `-1` and `0xffffffff`).
+fn parse_imm32(token: &str, cx: &LineCx) -> Result<i32, AsmError> {
+    let line_no = cx.line_no;
+    let val = parse_i64(token, cx)?;
+    if val < i32::MIN as i64 || val > u32::MAX as i64 {
+        return Err(AsmError::new(
+            line_no,
+            "imm-range",
+            format!("immediate out of range for 32 bits: '{token}'"),
+        )
+        .token(token));
+    }
+    Ok(val as u32 as i32)
+}
+
+/// Split a 32-bit value into `(hi20, lo12)` such that `(hi20 << 12) + lo12 == value` (wrapping),
+/// with `lo12` sign-extended as `addi`/`jalr`/loads interpret it.
+fn split_hi_lo(value: i32) -> (i32, i32) {
+    let lo = (value << 20) >> 20;
+    let hi = value.wrapping_sub(lo) >> 12;
+    (hi, lo)
+}
+
+/// `li rd, imm` for any 32-bit value: `addi`, `lui`, or `lui` + `addi`.
+fn expand_li(rd: u8, imm: i32) -> Vec<RiscvInstruction> {
+    if (-2048..=2047).contains(&imm) {
+        return vec![RiscvInstruction::IAlu {
+            op: RiscvOpcode::Add,
+            rd,
+            rs1: 0,
+            imm,
+        }];
+    }
+    let (hi, lo) = split_hi_lo(imm);
+    let mut seq = vec![RiscvInstruction::Lui { rd, imm: hi }];
+    if lo != 0 {
+        seq.push(RiscvInstruction::IAlu {
+            op: RiscvOpcode::Add,
+            rd,
+            rs1: rd,
+            imm: lo,
+        });
+    }
+    seq
+}
+
+/// Byte offsets encodable by a B-type branch (13-bit signed, even).
+const B_OFFSET_RANGE: RangeInclusive<i64> = -(1 << 12)..=(1 << 12) - 2;
+/// Byte offsets encodable by `jal` (21-bit signed, even).
+const J_OFFSET_RANGE: RangeInclusive<i64> = -(1 << 20)..=(1 << 20) - 2;
+
+/// Check a branch/jump byte offset is even and encodable.
+fn check_offset(
+    offset: i64,
+    range: RangeInclusive<i64>,
+    what: &str,
+    line_no: usize,
+    token: &str,
+) -> Result<i32, AsmError> {
+    if offset & 1 != 0 {
+        return Err(AsmError::new(line_no, "misaligned-offset", format!("{what} offset {offset} is odd"))
+            .token(token)
+            .hint("branch and jump offsets are multiples of 2 bytes"));
+    }
+    if !range.contains(&offset) {
+        return Err(AsmError::new(
+            line_no,
+            "offset-range",
+            format!("{what} offset {offset} out of range ({}..{})", range.start(), range.end()),
+        )
+        .token(token));
+    }
+    Ok(offset as i32)
+}
+
+/// `sym`, `sym+N` or `sym-N`.
+fn parse_symbol_ref(token: &str, cx: &LineCx) -> Result<(String, i32), AsmError> {
+    let t = token.trim().trim_end_matches(',');
+    let (name, addend) = match t.find(['+', '-']) {
+        Some(at) if at > 0 => (t[..at].trim(), parse_i32(&t[at..].replace(' ', ""), cx)?),
+        _ => (t, 0),
+    };
+    if !is_symbol_name(name) {
+        return Err(AsmError::new(cx.line_no, "bad-symbol", format!("expected a label, got '{name}'")).token(name));
+    }
+    Ok((name.to_string(), addend))
+}
+
+/// A label operand (or `label+N`) as a relocation of `kind`.
+fn symbol_reloc(kind: RelocKind, token: &str, cx: &LineCx) -> Result<Reloc, AsmError> {
+    let (symbol, addend) = parse_symbol_ref(token, cx)?;
+    Ok(Reloc { kind, symbol, addend })
+}
+
+/// Immediate operand that may use a relocation operator from `allowed`; plain values go
+/// through `plain`. `%hi`/`%lo` of a constant fold immediately.
+fn parse_imm_reloc(
+    token: &str,
+    cx: &LineCx,
+    allowed: &[RelocKind],
+    plain: fn(&str, &LineCx) -> Result<i32, AsmError>,
+) -> Result<Imm, AsmError> {
+    let line_no = cx.line_no;
+    let t = token.trim().trim_end_matches(',');
+    let Some(operator) = t.strip_prefix('%') else {
+        return Ok(Imm::Value(plain(t, cx)?));
+    };
+    let (name, inner) = operator
+        .split_once('(')
+        .and_then(|(name, rest)| Some((name.trim(), rest.strip_suffix(')')?.trim())))
+        .ok_or_else(|| {
+            AsmError::new(line_no, "bad-reloc", format!("malformed relocation operator '{t}'"))
+                .token(t)
+                .hint("expected %hi(sym), %lo(sym), %pcrel_hi(sym) or %pcrel_lo(label)")
+        })?;
+    let kind = match name {
+        "hi" => RelocKind::Hi20,
+        "lo" => RelocKind::Lo12,
+        "pcrel_hi" => RelocKind::PcrelHi20,
+        "pcrel_lo" => RelocKind::PcrelLo12,
+        _ => {
+            return Err(AsmError::new(line_no, "bad-reloc", format!("unknown relocation operator '%{name}'"))
+                .token(t)
+                .hint("expected %hi, %lo, %pcrel_hi or %pcrel_lo"))
+        }
+    };
+    if !allowed.contains(&kind) {
+        return Err(AsmError::new(line_no, "bad-reloc", format!("'%{name}' is not valid in this operand"))
+            .token(t)
+            .hint("use %hi with lui, %pcrel_hi with auipc, and %lo/%pcrel_lo in 12-bit immediates"));
+    }
+    if let (Ok(value), RelocKind::Hi20 | RelocKind::Lo12) = (parse_imm32(inner, cx), kind) {
+        let (hi, lo) = split_hi_lo(value);
+        return Ok(Imm::Value(if kind == RelocKind::Hi20 { hi } else { lo }));
+    }
+    Ok(Imm::Reloc(symbol_reloc(kind, inner, cx)?))
+}
+
+/// Relocations accepted in 12-bit I/S-type immediates.
+const LO12_RELOCS: &[RelocKind] = &[RelocKind::Lo12, RelocKind::PcrelLo12];
+
+/// A `.equ` name where a branch or jump expects a label. Numeric literals are pc-relative offsets,
+/// but a constant almost always holds an address, so it is rejected rather than read as one.
+fn check_not_const(target: &str, cx: &LineCx) -> Result<(), AsmError> {
+    let name = target.trim().trim_end_matches(',');
+    if cx.consts.contains_key(name) {
+        return Err(AsmError::new(
+            cx.line_no,
+            "const-target",
+            format!("'{name}' is a .equ constant, not a label"),
+        )
+        .token(name)
+        .hint("targets must be labels or literal pc-relative offsets; use `li` + `jalr` for an absolute address"));
+    }
+    Ok(())
+}
+
+/// Branch to `target`, which is either a numeric offset or a label.
+fn branch_to(cond: BranchCondition, rs1: u8, rs2: u8, target: &str, cx: &LineCx) -> Result<PendingInstr, AsmError> {
+    check_not_const(target, cx)?;
+    let instr = RiscvInstruction::Branch { cond, rs1, rs2, imm: 0 };
+    Ok(match parse_i32(target, cx) {
+        Ok(imm) => imm_instr(
+            instr,
+            Imm::Value(check_offset(imm as i64, B_OFFSET_RANGE, "branch", cx.line_no, target)?),
+        ),
+        Err(_) => imm_instr(instr, Imm::Reloc(symbol_reloc(RelocKind::Branch, target, cx)?)),
+    })
+}
+
+/// `jal rd, target`, where `target` is either a numeric offset or a label.
+fn jal_to(rd: u8, target: &str, cx: &LineCx) -> Result<PendingInstr, AsmError> {
+    check_not_const(target, cx)?;
+    let instr = RiscvInstruction::Jal { rd, imm: 0 };
+    Ok(match parse_i32(target, cx) {
+        Ok(imm) => imm_instr(
+            instr,
+            Imm::Value(check_offset(imm as i64, J_OFFSET_RANGE, "jal", cx.line_no, target)?),
+        ),
+        Err(_) => imm_instr(instr, Imm::Reloc(symbol_reloc(RelocKind::Jal, target, cx)?)),
+    })
+}
+
+/// `auipc tmp, %pcrel_hi(sym)` followed by `lo` with `%pcrel_lo` of the same offset.
+fn pcrel_pair(tmp: u8, lo: RiscvInstruction, target: &str, cx: &LineCx) -> Result<PendingInstr, AsmError> {
+    let hi = symbol_reloc(RelocKind::PcrelHi20, target, cx)?;
+    let lo_reloc = Reloc {
+        kind: RelocKind::PcrelLo12Paired,
+        ..hi.clone()
+    };
+    Ok(PendingInstr::Reloc(vec![
+        RelocWord {
+            instr: RiscvInstruction::Auipc { rd: tmp, imm: 0 },
+            reloc: hi,
+        },
+        RelocWord { instr: lo, reloc: lo_reloc },
+    ]))
+}
+
+/// The condition that is true exactly when `cond` is false.
+fn invert_cond(cond: BranchCondition) -> BranchCondition {
+    match cond {
+        BranchCondition::Eq => BranchCondition::Ne,
+        BranchCondition::Ne => BranchCondition::Eq,
+        BranchCondition::Lt => BranchCondition::Ge,
+        BranchCondition::Ge => BranchCondition::Lt,
+        BranchCondition::Ltu => BranchCondition::Geu,
+        BranchCondition::Geu => BranchCondition::Ltu,
+    }
+}
+
+/// Branch pseudo-instructions: `(cond, swap operands)` for two-register forms and
+/// `(cond, zero on the left)` for the compare-with-zero forms.
+fn branch_pseudo(op: &str) -> Option<(BranchCondition, bool)> {
+    Some(match op {
+        "bgt" => (BranchCondition::Lt, true),
+        "ble" => (BranchCondition::Ge, true),
+        "bgtu" => (BranchCondition::Ltu, true),
+        "bleu" => (BranchCondition::Geu, true),
+        _ => return None,
+    })
+}
+
+fn branch_zero_pseudo(op: &str) -> Option<(BranchCondition, bool)> {
+    Some(match op {
+        "beqz" => (BranchCondition::Eq, false),
+        "bnez" => (BranchCondition::Ne, false),
+        "bltz" => (BranchCondition::Lt, false),
+        "bgez" => (BranchCondition::Ge, false),
+        "blez" => (BranchCondition::Ge, true),
+        "bgtz" => (BranchCondition::Lt, true),
+        _ => return None,
+    })
+}
+
+/// Parse an immediate and check it against `min..=max` (inclusive).
+fn parse_imm_in(token: &str, cx: &LineCx, min: i32, max: i32, what: &str) -> Result<i32, AsmError> {
+    let line_no = cx.line_no;
+    let imm = parse_i32(token, cx)?;
+    if imm < min || imm > max {
+        return Err(AsmError::new(
+            line_no,
+            "imm-range",
+            format!("{what} '{}' out of range ({min}..{max})", token.trim()),
+        )
+        .token(token));
+    }
+    Ok(imm)
+}
+
+/// 12-bit signed immediate (I-type and S-type).
+fn parse_imm12(token: &str, cx: &LineCx) -> Result<i32, AsmError> {
+    parse_imm_in(token, cx, -2048, 2047, "12-bit immediate")
+}
+
+/// 20-bit upper immediate (U-type), returned sign-extended the way the decoder reports it.
+fn parse_imm20(token: &str, cx: &LineCx) -> Result<i32, AsmError> {
+    let imm = parse_imm_in(token, cx, -(1 << 19), (1 << 20) - 1, "20-bit upper immediate")?;
+    Ok((imm << 12) >> 12)
+}
+
+fn parse_shamt(token: &str, cx: &LineCx) -> Result<i32, AsmError> {
+    parse_imm_in(token, cx, 0, 31, "shift amount")
+}
+
+fn expect_operands(op: &str, args: &[&str], n: usize, usage: &str, line_no: usize) -> Result<(), AsmError> {
+    if args.len() != n {
+        let plural = if n == 1 { "" } else { "s" };
+        return Err(AsmError::new(
+            line_no,
+            "operand-count",
+            format!("{op} expects {n} operand{plural}: {usage}"),
+        ));
+    }
+    Ok(())
+}
+
+fn r_type_op(op: &str) -> Option<RiscvOpcode> {
+    Some(match op {
+        "add" => RiscvOpcode::Add,
+        "sub" => RiscvOpcode::Sub,
+        "and" => RiscvOpcode::And,
+        "or" => RiscvOpcode::Or,
+        "xor" => RiscvOpcode::Xor,
+        "sll" => RiscvOpcode::Sll,
+        "srl" => RiscvOpcode::Srl,
+        "sra" => RiscvOpcode::Sra,
+        "slt" => RiscvOpcode::Slt,
+        "sltu" => RiscvOpcode::Sltu,
+        _ => return None,
+    })
+}
+
+/// RV32M multiply/divide (R-type encoding, funct7 = 1).
+fn m_ext_op(op: &str) -> Option<RiscvOpcode> {
+    Some(match op {
+        "mul" => RiscvOpcode::Mul,
+        "mulh" => RiscvOpcode::Mulh,
+        "mulhu" => RiscvOpcode::Mulhu,
+        "mulhsu" => RiscvOpcode::Mulhsu,
+        "div" => RiscvOpcode::Div,
+        "divu" => RiscvOpcode::Divu,
+        "rem" => RiscvOpcode::Rem,
+        "remu" => RiscvOpcode::Remu,
+        _ => return None,
+    })
+}
+
+fn i_type_op(op: &str) -> Option<RiscvOpcode> {
+    Some(match op {
+        "addi" => RiscvOpcode::Add,
+        "andi" => RiscvOpcode::And,
+        "ori" => RiscvOpcode::Or,
+        "xori" => RiscvOpcode::Xor,
+        "slti" => RiscvOpcode::Slt,
+        "sltiu" => RiscvOpcode::Sltu,
+        _ => return None,
+    })
+}
+
+fn shift_imm_op(op: &str) -> Option<RiscvOpcode> {
+    Some(match op {
+        "slli" => RiscvOpcode::Sll,
+        "srli" => RiscvOpcode::Srl,
+        "srai" => RiscvOpcode::Sra,
+        _ => return None,
+    })
+}
+
+fn branch_cond(op: &str) -> Option<BranchCondition> {
+    Some(match op {
+        "beq" => BranchCondition::Eq,
+        "bne" => BranchCondition::Ne,
+        "blt" => BranchCondition::Lt,
+        "bge" => BranchCondition::Ge,
+        "bltu" => BranchCondition::Ltu,
+        "bgeu" => BranchCondition::Geu,
+        _ => return None,
+    })
+}
+
+fn load_op(op: &str) -> Option<RiscvMemOp> {
+    Some(match op {
+        "lb" => RiscvMemOp::Lb,
+        "lh" => RiscvMemOp::Lh,
+        "lw" => RiscvMemOp::Lw,
+        "lbu" => RiscvMemOp::Lbu,
+        "lhu" => RiscvMemOp::Lhu,
+        _ => return None,
+    })
+}
+
+fn store_op(op: &str) -> Option<RiscvMemOp> {
+    Some(match op {
+        "sb" => RiscvMemOp::Sb,
+        "sh" => RiscvMemOp::Sh,
+        "sw" => RiscvMemOp::Sw,
+        _ => return None,
+    })
+}
+
 fn parse_u32_word(line: &str) -> Option<u32> {
     let s0 = line.trim().replace('_', "");
     if s0.is_empty() {
@@ -118,288 +735,1849 @@ fn parse_u32_word(line: &str) -> Option<u32> {
     u32::from_str_radix(s, radix).ok()
 }
 
-fn parse_mem_operand(token: &str, line_no: usize) -> Result<(i32, u8), String> {
+/// `off(rs1)`, where `off` may be `%lo(sym)` / `%pcrel_lo(label)`.
+fn parse_mem_operand(token: &str, cx: &LineCx) -> Result<(Imm, u8), AsmError> {
+    let line_no = cx.line_no;
     let t = token.trim().trim_end_matches(',');
-    let open = t
-        .find('(')
-        .ok_or_else(|| format!("line {line_no}: expected mem operand like '0(x1)', got '{token}'"))?;
-    let close = t
-        .rfind(')')
-        .ok_or_else(|| format!("line {line_no}: expected mem operand like '0(x1)', got '{token}'"))?;
+    let open = t.rfind('(').ok_or_else(|| {
+        AsmError::new(
+            line_no,
+            "bad-mem-operand",
+            format!("expected mem operand like '0(x1)', got '{token}'"),
+        )
+        .token(token)
+    })?;
+    let close = t.rfind(')').ok_or_else(|| {
+        AsmError::new(
+            line_no,
+            "bad-mem-operand",
+            format!("expected mem operand like '0(x1)', got '{token}'"),
+        )
+        .token(token)
+    })?;
     if close <= open {
-        return Err(format!("line {line_no}: invalid mem operand '{token}'"));
+        return Err(AsmError::new(line_no, "bad-mem-operand", format!("invalid mem operand '{token}'")).token(token));
     }
     let off_str = t[..open].trim();
     let base_str = t[open + 1..close].trim();
-    let off = if off_str.is_empty() { 0 } else { parse_i32(off_str, line_no)? };
+    let off = if off_str.is_empty() {
+        Imm::Value(0)
+    } else {
+        parse_imm_reloc(off_str, cx, LO12_RELOCS, parse_imm12)?
+    };
     let base = parse_reg(base_str, line_no)?;
     Ok((off, base))
 }
 
 fn parse_operands(rest: &str) -> Vec<&str> {
-    rest.split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect()
+    rest.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect()
 }
 
-fn parse_line(
-    line_no: usize,
-    line: &str,
-    labels: &mut HashMap<String, usize>,
-    out: &mut Vec<PendingLine>,
-) -> Result<(), String> {
-    let raw = strip_comment(line).trim();
-    if raw.is_empty() {
-        return Ok(());
-    }
-    if raw.starts_with('.') {
-        return Ok(());
-    }
-
-    let mut rest = raw;
-    if let Some((label, after)) = raw.split_once(':') {
-        let name = label.trim();
-        if !name.is_empty() {
-            let idx = out.len();
-            labels.insert(name.to_string(), idx);
-        }
-        rest = after.trim();
-        if rest.is_empty() {
-            return Ok(());
-        }
-    }
-
+/// Parse one instruction (mnemonic + operands, or a raw 32-bit word) in the text section.
+fn parse_instruction(rest: &str, cx: &LineCx) -> Result<PendingInstr, AsmError> {
+    let line_no = cx.line_no;
     if let Some(word) = parse_u32_word(rest) {
-        let decoded = deprecated_neo_memory::riscv::lookups::decode_instruction(word)
-            .map_err(|e| format!("line {line_no}: invalid RV32 word '{rest}': {e}"))?;
+        let decoded = deprecated_neo_memory::riscv::lookups::decode_instruction(word).map_err(|e| {
+            AsmError::new(line_no, "bad-raw-word", format!("invalid RV32 word '{rest}': {e}")).token(rest)
+        })?;
         // Ensure our round-trip matches the original encoding.
         let expected = word.to_le_bytes();
-        let re_bytes = encode_program(&[decoded.clone()]);
-        if re_bytes.len() != 4 || re_bytes.as_slice() != &expected {
-            return Err(format!(
-                "line {line_no}: unsupported raw word '{rest}' (decode/encode mismatch)"
-            ));
+        let re_bytes = encode_program(std::slice::from_ref(&decoded));
+        if re_bytes.len() != 4 || re_bytes.as_slice() != expected {
+            return Err(AsmError::new(
+                line_no,
+                "bad-raw-word",
+                format!("unsupported raw word '{rest}' (decode/encode mismatch)"),
+            )
+            .token(rest));
         }
-        out.push(PendingLine {
-            line_no,
-            instr: PendingInstr::Resolved(decoded),
-        });
-        return Ok(());
+        return Ok(PendingInstr::Resolved(decoded));
     }
 
     let mut iter = rest.split_whitespace();
     let op = iter
         .next()
-        .ok_or_else(|| format!("line {line_no}: expected instruction"))?
+        .ok_or_else(|| AsmError::new(line_no, "expected-instruction", "expected instruction"))?
         .to_ascii_lowercase();
     let operands_str = iter.collect::<Vec<_>>().join(" ");
     let args = parse_operands(&operands_str);
 
-    let pending = match op.as_str() {
-        "addi" => {
-            if args.len() != 3 {
-                return Err(format!("line {line_no}: addi expects 3 operands: addi rd, rs1, imm"));
-            }
-            let rd = parse_reg(args[0], line_no)?;
-            let rs1 = parse_reg(args[1], line_no)?;
-            let imm = parse_i32(args[2], line_no)?;
-            PendingInstr::Resolved(RiscvInstruction::IAlu {
-                op: RiscvOpcode::Add,
-                rd,
-                rs1,
-                imm,
-            })
-        }
-        "add" => {
-            if args.len() != 3 {
-                return Err(format!("line {line_no}: add expects 3 operands: add rd, rs1, rs2"));
-            }
-            let rd = parse_reg(args[0], line_no)?;
-            let rs1 = parse_reg(args[1], line_no)?;
-            let rs2 = parse_reg(args[2], line_no)?;
-            PendingInstr::Resolved(RiscvInstruction::RAlu {
-                op: RiscvOpcode::Add,
-                rd,
-                rs1,
-                rs2,
-            })
-        }
-        "lw" => {
-            if args.len() != 2 {
-                return Err(format!("line {line_no}: lw expects 2 operands: lw rd, off(rs1)"));
-            }
-            let rd = parse_reg(args[0], line_no)?;
-            let (imm, rs1) = parse_mem_operand(args[1], line_no)?;
-            PendingInstr::Resolved(RiscvInstruction::Load {
-                op: RiscvMemOp::Lw,
-                rd,
-                rs1,
-                imm,
-            })
-        }
-        "sw" => {
-            if args.len() != 2 {
-                return Err(format!("line {line_no}: sw expects 2 operands: sw rs2, off(rs1)"));
-            }
-            let rs2 = parse_reg(args[0], line_no)?;
-            let (imm, rs1) = parse_mem_operand(args[1], line_no)?;
-            PendingInstr::Resolved(RiscvInstruction::Store {
-                op: RiscvMemOp::Sw,
-                rs1,
-                rs2,
-                imm,
-            })
-        }
-        "beq" => {
-            if args.len() != 3 {
-                return Err(format!(
-                    "line {line_no}: beq expects 3 operands: beq rs1, rs2, label|imm"
-                ));
-            }
-            let rs1 = parse_reg(args[0], line_no)?;
-            let rs2 = parse_reg(args[1], line_no)?;
-            match parse_i32(args[2], line_no) {
-                Ok(imm) => PendingInstr::Resolved(RiscvInstruction::Branch {
-                    cond: BranchCondition::Eq,
-                    rs1,
-                    rs2,
-                    imm,
+    let op = op.as_str();
+    let pending = if let Some(alu) = r_type_op(op).or_else(|| m_ext_op(op)) {
+        expect_operands(op, &args, 3, &format!("{op} rd, rs1, rs2"), line_no)?;
+        PendingInstr::Resolved(RiscvInstruction::RAlu {
+            op: alu,
+            rd: parse_reg(args[0], line_no)?,
+            rs1: parse_reg(args[1], line_no)?,
+            rs2: parse_reg(args[2], line_no)?,
+        })
+    } else if let Some(alu) = i_type_op(op) {
+        expect_operands(op, &args, 3, &format!("{op} rd, rs1, imm"), line_no)?;
+        let instr = RiscvInstruction::IAlu {
+            op: alu,
+            rd: parse_reg(args[0], line_no)?,
+            rs1: parse_reg(args[1], line_no)?,
+            imm: 0,
+        };
+        imm_instr(instr, parse_imm_reloc(args[2], cx, LO12_RELOCS, parse_imm12)?)
+    } else if let Some(alu) = shift_imm_op(op) {
+        expect_operands(op, &args, 3, &format!("{op} rd, rs1, shamt"), line_no)?;
+        PendingInstr::Resolved(RiscvInstruction::IAlu {
+            op: alu,
+            rd: parse_reg(args[0], line_no)?,
+            rs1: parse_reg(args[1], line_no)?,
+            imm: parse_shamt(args[2], cx)?,
+        })
+    } else if let Some(mem) = load_op(op) {
+        expect_operands(op, &args, 2, &format!("{op} rd, off(rs1)"), line_no)?;
+        let rd = parse_reg(args[0], line_no)?;
+        let (imm, rs1) = parse_mem_operand(args[1], cx)?;
+        imm_instr(RiscvInstruction::Load { op: mem, rd, rs1, imm: 0 }, imm)
+    } else if let Some(mem) = store_op(op) {
+        expect_operands(op, &args, 2, &format!("{op} rs2, off(rs1)"), line_no)?;
+        let rs2 = parse_reg(args[0], line_no)?;
+        let (imm, rs1) = parse_mem_operand(args[1], cx)?;
+        imm_instr(RiscvInstruction::Store { op: mem, rs1, rs2, imm: 0 }, imm)
+    } else if let Some(cond) = branch_cond(op) {
+        expect_operands(op, &args, 3, &format!("{op} rs1, rs2, label|imm"), line_no)?;
+        let rs1 = parse_reg(args[0], line_no)?;
+        let rs2 = parse_reg(args[1], line_no)?;
+        branch_to(cond, rs1, rs2, args[2], cx)?
+    } else if let Some((cond, swap)) = branch_pseudo(op) {
+        expect_operands(op, &args, 3, &format!("{op} rs1, rs2, label|imm"), line_no)?;
+        let rs1 = parse_reg(args[0], line_no)?;
+        let rs2 = parse_reg(args[1], line_no)?;
+        let (a, b) = if swap { (rs2, rs1) } else { (rs1, rs2) };
+        branch_to(cond, a, b, args[2], cx)?
+    } else if let Some((cond, zero_first)) = branch_zero_pseudo(op) {
+        expect_operands(op, &args, 2, &format!("{op} rs, label|imm"), line_no)?;
+        let rs = parse_reg(args[0], line_no)?;
+        let (a, b) = if zero_first { (0, rs) } else { (rs, 0) };
+        branch_to(cond, a, b, args[1], cx)?
+    } else {
+        match op {
+            "lui" | "auipc" => {
+                expect_operands(op, &args, 2, &format!("{op} rd, imm20"), line_no)?;
+                let rd = parse_reg(args[0], line_no)?;
+                let (instr, reloc) = if op == "lui" {
+                    (RiscvInstruction::Lui { rd, imm: 0 }, RelocKind::Hi20)
+                } else {
+                    (RiscvInstruction::Auipc { rd, imm: 0 }, RelocKind::PcrelHi20)
+                };
+                imm_instr(instr, parse_imm_reloc(args[1], cx, &[reloc], parse_imm20)?)
+            }
+            "jalr" => match args.len() {
+                // jalr rs1  (rd=ra, imm=0)
+                1 => PendingInstr::Resolved(RiscvInstruction::Jalr {
+                    rd: 1,
+                    rs1: parse_reg(args[0], line_no)?,
+                    imm: 0,
                 }),
-                Err(_) => PendingInstr::Beq {
-                    rs1,
-                    rs2,
-                    target: args[2].to_string(),
-                },
-            }
-        }
-        "jal" => {
-            if args.len() == 1 {
-                // pseudo: jal label  (rd=ra)
-                let rd = 1u8;
-                let target = args[0].to_string();
-                PendingInstr::Jal { rd, target }
-            } else if args.len() == 2 {
+                // jalr rd, off(rs1)
+                2 => {
+                    let rd = parse_reg(args[0], line_no)?;
+                    let (imm, rs1) = parse_mem_operand(args[1], cx)?;
+                    imm_instr(RiscvInstruction::Jalr { rd, rs1, imm: 0 }, imm)
+                }
+                // jalr rd, rs1, imm
+                3 => {
+                    let instr = RiscvInstruction::Jalr {
+                        rd: parse_reg(args[0], line_no)?,
+                        rs1: parse_reg(args[1], line_no)?,
+                        imm: 0,
+                    };
+                    imm_instr(instr, parse_imm_reloc(args[2], cx, LO12_RELOCS, parse_imm12)?)
+                }
+                _ => {
+                    return Err(AsmError::new(
+                        line_no,
+                        "operand-count",
+                        "jalr expects 1..3 operands: jalr rs1  OR  jalr rd, off(rs1)  OR  jalr rd, rs1, imm",
+                    ))
+                }
+            },
+            "jal" => {
+                if args.len() == 1 {
+                    // pseudo: jal label  (rd=ra)
+                    jal_to(1, args[0], cx)?
+                } else if args.len() == 2 {
+                    jal_to(parse_reg(args[0], line_no)?, args[1], cx)?
+                } else {
+                    return Err(AsmError::new(
+                        line_no,
+                        "operand-count",
+                        "jal expects 1 or 2 operands: jal label  OR  jal rd, label|imm",
+                    ));
+                }
+            }
+            "j" => {
+                expect_operands(op, &args, 1, "j label", line_no)?;
+                jal_to(0, args[0], cx)?
+            }
+            "li" => {
+                expect_operands(op, &args, 2, "li rd, imm", line_no)?;
+                let rd = parse_reg(args[0], line_no)?;
+                let imm = parse_imm32(args[1], cx)?;
+                PendingInstr::Expanded(expand_li(rd, imm))
+            }
+            "la" => {
+                expect_operands(op, &args, 2, "la rd, label", line_no)?;
                 let rd = parse_reg(args[0], line_no)?;
-                match parse_i32(args[1], line_no) {
-                    Ok(imm) => PendingInstr::Resolved(RiscvInstruction::Jal { rd, imm }),
-                    Err(_) => PendingInstr::Jal {
+                let addi = RiscvInstruction::IAlu {
+                    op: RiscvOpcode::Add,
+                    rd,
+                    rs1: rd,
+                    imm: 0,
+                };
+                pcrel_pair(rd, addi, args[1], cx)?
+            }
+            "call" | "tail" => {
+                expect_operands(op, &args, 1, &format!("{op} label"), line_no)?;
+                // Standard expansions: call uses ra as both scratch and link, tail clobbers t1.
+                let (link, tmp) = if op == "call" { (1, 1) } else { (0, 6) };
+                let jalr = RiscvInstruction::Jalr {
+                    rd: link,
+                    rs1: tmp,
+                    imm: 0,
+                };
+                check_not_const(args[0], cx)?;
+                pcrel_pair(tmp, jalr, args[0], cx)?
+            }
+            "ret" => {
+                expect_operands(op, &args, 0, "ret", line_no)?;
+                PendingInstr::Resolved(RiscvInstruction::Jalr { rd: 0, rs1: 1, imm: 0 })
+            }
+            "jr" => {
+                expect_operands(op, &args, 1, "jr rs", line_no)?;
+                PendingInstr::Resolved(RiscvInstruction::Jalr {
+                    rd: 0,
+                    rs1: parse_reg(args[0], line_no)?,
+                    imm: 0,
+                })
+            }
+            "not" | "neg" | "seqz" | "snez" | "sltz" | "sgtz" => {
+                expect_operands(op, &args, 2, &format!("{op} rd, rs"), line_no)?;
+                let rd = parse_reg(args[0], line_no)?;
+                let rs = parse_reg(args[1], line_no)?;
+                PendingInstr::Resolved(match op {
+                    "not" => RiscvInstruction::IAlu {
+                        op: RiscvOpcode::Xor,
+                        rd,
+                        rs1: rs,
+                        imm: -1,
+                    },
+                    "neg" => RiscvInstruction::RAlu {
+                        op: RiscvOpcode::Sub,
+                        rd,
+                        rs1: 0,
+                        rs2: rs,
+                    },
+                    "seqz" => RiscvInstruction::IAlu {
+                        op: RiscvOpcode::Sltu,
+                        rd,
+                        rs1: rs,
+                        imm: 1,
+                    },
+                    "snez" => RiscvInstruction::RAlu {
+                        op: RiscvOpcode::Sltu,
                         rd,
-                        target: args[1].to_string(),
+                        rs1: 0,
+                        rs2: rs,
                     },
+                    "sltz" => RiscvInstruction::RAlu {
+                        op: RiscvOpcode::Slt,
+                        rd,
+                        rs1: rs,
+                        rs2: 0,
+                    },
+                    _ => RiscvInstruction::RAlu {
+                        op: RiscvOpcode::Slt,
+                        rd,
+                        rs1: 0,
+                        rs2: rs,
+                    },
+                })
+            }
+            "mv" => {
+                expect_operands(op, &args, 2, "mv rd, rs", line_no)?;
+                let rd = parse_reg(args[0], line_no)?;
+                let rs = parse_reg(args[1], line_no)?;
+                PendingInstr::Resolved(RiscvInstruction::IAlu {
+                    op: RiscvOpcode::Add,
+                    rd,
+                    rs1: rs,
+                    imm: 0,
+                })
+            }
+            "ecall" | "halt" => {
+                if !args.is_empty() {
+                    return Err(AsmError::new(
+                        line_no,
+                        "operand-count",
+                        format!("{op} takes no operands"),
+                    ));
+                }
+                PendingInstr::Resolved(RiscvInstruction::Halt)
+            }
+            "nop" => {
+                if !args.is_empty() {
+                    return Err(AsmError::new(line_no, "operand-count", "nop takes no operands"));
+                }
+                PendingInstr::Resolved(RiscvInstruction::Nop)
+            }
+            _ => {
+                return Err(
+                    AsmError::new(line_no, "unknown-opcode", format!("unsupported opcode '{op}'"))
+                        .token(op)
+                        .hint("supported: RV32I except fence/ebreak, RV32M, and the standard pseudo-instructions"),
+                )
+            }
+        }
+    };
+
+    Ok(pending)
+}
+
+/// RAM address where the `.data` section is placed.
+pub const DEFAULT_DATA_BASE: u32 = 0x400;
+
+/// Where the assembler places `.text` and `.data`, plus constants the program can use as if
+/// defined with `.equ` (e.g. memory-map bounds).
+#[derive(Debug, Clone)]
+pub struct Rv32AsmLayout {
+    pub text_base: u32,
+    pub data_base: u32,
+    pub symbols: Vec<(String, i64)>,
+    pub ecall_shim: Option<Rv32EcallShim>,
+}
+
+impl Default for Rv32AsmLayout {
+    fn default() -> Self {
+        Rv32AsmLayout {
+            text_base: 0,
+            data_base: DEFAULT_DATA_BASE,
+            symbols: Vec::new(),
+            ecall_shim: None,
+        }
+    }
+}
+
+/// In-guest `ecall` handler: every `ecall` in the program assembles to `sw t6, t6_slot(x0);
+/// jal t6, entry; lw t6, t6_slot(x0)`, so the caller's `t6` survives the call, and `source` is
+/// linked into `.text` after the program. Its lines report line 0, and its own halts must use
+/// `halt`.
+#[derive(Debug, Clone)]
+pub struct Rv32EcallShim {
+    pub entry: &'static str,
+    pub source: &'static str,
+    /// `x0`-relative offset of the word that holds the caller's `t6` across the call.
+    pub t6_slot: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Section {
+    #[default]
+    Text,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Symbol {
+    /// Text label: index of the next item in `Assembler::items`.
+    Text(usize),
+    /// Data label: byte offset into the `.data` section.
+    Data(usize),
+}
+
+/// `.word label` in `.data`, patched once addresses are known.
+#[derive(Debug)]
+struct DataFixup {
+    line_no: usize,
+    offset: usize,
+    target: String,
+}
+
+/// Assembled guest image: a ROM (text) plus RAM initialization (data).
+#[derive(Debug, Clone, Default)]
+pub struct Rv32Program {
+    pub rom: Vec<u8>,
+    /// `(addr, value)` word writes applied to RAM before execution (non-zero `.data` words).
+    pub ram_init: Vec<(u32, u32)>,
+    /// Label addresses (text and data), sorted by address.
+    pub symbols: Vec<(String, u32)>,
+    /// 1-based source line of each ROM word (pseudo-instruction expansions repeat their line;
+    /// a linked `Rv32EcallShim` reports 0).
+    pub lines: Vec<usize>,
+}
+
+impl Rv32Program {
+    /// Source line of the ROM word at `pc`, for a program whose `.text` starts at `text_base`.
+    pub fn line_at(&self, text_base: u32, pc: u32) -> Option<usize> {
+        let off = pc.checked_sub(text_base)?;
+        if off & 3 != 0 {
+            return None;
+        }
+        self.lines.get(off as usize / 4).copied().filter(|&line| line > 0)
+    }
+}
+
+#[derive(Default)]
+struct Assembler {
+    section: Section,
+    symbols: HashMap<String, Symbol>,
+    /// Line each label was defined on, for duplicate-label errors and unused-label warnings.
+    label_lines: HashMap<String, usize>,
+    /// Names declared with `.globl`; they count as used.
+    globals: HashSet<String>,
+    consts: HashMap<String, i64>,
+    /// Constants the layout predefines; user code may not redefine them.
+    predefined: HashSet<String>,
+    /// `ecall` target and `t6` save slot when a shim is linked.
+    ecall_shim: Option<(&'static str, i32)>,
+    items: Vec<PendingLine>,
+    data: Vec<u8>,
+    data_fixups: Vec<DataFixup>,
+    diagnostics: Vec<(Severity, AsmError)>,
+}
+
+impl Assembler {
+    fn parse_line(&mut self, line_no: usize, line: &str) -> Result<(), AsmError> {
+        let raw = strip_comment(line).trim();
+        if raw.is_empty() {
+            return Ok(());
+        }
+
+        let mut rest = raw;
+        if let Some((label, after)) = raw.split_once(':') {
+            let name = label.trim();
+            if !name.is_empty() {
+                let sym = match self.section {
+                    Section::Text => Symbol::Text(self.items.len()),
+                    Section::Data => Symbol::Data(self.data.len()),
+                };
+                if let Err(e) = self.check_reserved(line_no, name) {
+                    self.error(e);
+                } else if let Some(&first) = self.label_lines.get(name) {
+                    self.error(
+                        AsmError::new(
+                            line_no,
+                            "duplicate-label",
+                            format!("label '{name}' is already defined on line {first}"),
+                        )
+                        .token(name)
+                        .hint("rename one of the labels"),
+                    );
+                } else {
+                    self.label_lines.insert(name.to_string(), line_no);
+                    self.symbols.insert(name.to_string(), sym);
+                }
+            }
+            rest = after.trim();
+            if rest.is_empty() {
+                return Ok(());
+            }
+        }
+
+        if rest.starts_with('.') {
+            return self.parse_directive(line_no, rest);
+        }
+
+        if self.section == Section::Data {
+            return Err(
+                AsmError::new(line_no, "wrong-section", "instructions are not allowed in .data")
+                    .hint("switch back with '.text'"),
+            );
+        }
+        let cx = LineCx {
+            line_no,
+            consts: &self.consts,
+        };
+        let instrs = match self.ecall_shim {
+            Some((entry, slot)) if rest.eq_ignore_ascii_case("ecall") => vec![
+                parse_instruction(&format!("sw t6, {slot}(x0)"), &cx)?,
+                jal_to(31, entry, &cx)?,
+                parse_instruction(&format!("lw t6, {slot}(x0)"), &cx)?,
+            ],
+            _ => vec![parse_instruction(rest, &cx)?],
+        };
+        self.items.extend(instrs.into_iter().map(|instr| PendingLine { line_no, instr }));
+        Ok(())
+    }
+
+    /// Error if user code (`line_no > 0`) defines `name`, which belongs to the layout or the
+    /// ecall shim: a predefined constant, or any name starting with `__neo_`.
+    fn check_reserved(&self, line_no: usize, name: &str) -> Result<(), AsmError> {
+        if line_no == 0 || !(self.predefined.contains(name) || name.starts_with("__neo_")) {
+            return Ok(());
+        }
+        Err(AsmError::new(line_no, "reserved-name", format!("'{name}' is reserved"))
+            .token(name)
+            .hint("memory map and syscall constants, and `__neo_*` names, are predefined; pick another name"))
+    }
+
+    fn parse_directive(&mut self, line_no: usize, rest: &str) -> Result<(), AsmError> {
+        let (name, operands) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let name = name.to_ascii_lowercase();
+        let args = parse_operands(operands);
+
+        match name.as_str() {
+            ".text" => self.section = Section::Text,
+            ".data" | ".rodata" | ".bss" => self.section = Section::Data,
+            ".section" => {
+                expect_operands(&name, &args, 1, ".section .text|.data|.rodata|.bss", line_no)?;
+                self.section = match args[0] {
+                    ".text" => Section::Text,
+                    ".data" | ".rodata" | ".bss" | ".sdata" | ".sbss" => Section::Data,
+                    other => {
+                        return Err(
+                            AsmError::new(line_no, "bad-directive", format!("unsupported section '{other}'"))
+                                .token(other),
+                        )
+                    }
+                };
+            }
+            // Single-file programs have no linker, so visibility only marks the symbols as used.
+            ".globl" | ".global" => self.globals.extend(args.iter().map(|a| a.to_string())),
+            ".equ" | ".set" => {
+                expect_operands(&name, &args, 2, &format!("{name} name, value"), line_no)?;
+                let cx = LineCx {
+                    line_no,
+                    consts: &self.consts,
+                };
+                let value = parse_i64(args[1], &cx)?;
+                self.check_reserved(line_no, args[0])?;
+                self.consts.insert(args[0].to_string(), value);
+            }
+            ".word" | ".half" | ".byte" => {
+                self.require_data(&name, line_no)?;
+                if args.is_empty() {
+                    return Err(AsmError::new(
+                        line_no,
+                        "bad-directive",
+                        format!("{name} expects at least one value"),
+                    ));
+                }
+                let width = match name.as_str() {
+                    ".word" => 4,
+                    ".half" => 2,
+                    _ => 1,
+                };
+                for arg in args {
+                    self.emit_data_value(line_no, arg, width)?;
                 }
-            } else {
-                return Err(format!(
-                    "line {line_no}: jal expects 1 or 2 operands: jal label  OR  jal rd, label|imm"
-                ));
             }
+            ".space" | ".zero" => {
+                self.require_data(&name, line_no)?;
+                if args.is_empty() || args.len() > 2 {
+                    return Err(AsmError::new(
+                        line_no,
+                        "bad-directive",
+                        format!("{name} expects: {name} size[, fill]"),
+                    ));
+                }
+                let cx = LineCx {
+                    line_no,
+                    consts: &self.consts,
+                };
+                let size = parse_i64(args[0], &cx)?;
+                if !(0..=1 << 20).contains(&size) {
+                    return Err(AsmError::new(
+                        line_no,
+                        "imm-range",
+                        format!("{name} size {size} out of range (0..1048576)"),
+                    )
+                    .token(args[0]));
+                }
+                let fill = match args.get(1) {
+                    Some(f) => parse_imm_in(f, &cx, -128, 255, "fill byte")? as u8,
+                    None => 0,
+                };
+                self.data.resize(self.data.len() + size as usize, fill);
+            }
+            ".align" | ".p2align" | ".balign" => {
+                expect_operands(&name, &args, 1, &format!("{name} n"), line_no)?;
+                let cx = LineCx {
+                    line_no,
+                    consts: &self.consts,
+                };
+                // `.align`/`.p2align` take a power of two (RISC-V convention); `.balign` takes bytes.
+                let align = if name == ".balign" {
+                    let n = parse_imm_in(args[0], &cx, 1, 4096, "alignment")?;
+                    if !(n as u32).is_power_of_two() {
+                        return Err(AsmError::new(
+                            line_no,
+                            "bad-directive",
+                            format!(".balign {n} is not a power of two"),
+                        )
+                        .token(args[0]));
+                    }
+                    n as usize
+                } else {
+                    1usize << parse_imm_in(args[0], &cx, 0, 12, "alignment exponent")?
+                };
+                self.align(align, line_no);
+            }
+            _ => return Err(
+                AsmError::new(line_no, "unknown-directive", format!("unsupported directive '{name}'"))
+                    .token(&name)
+                    .hint(
+                        "supported: .text, .data, .section, .globl, .equ, .word, .half, .byte, .space, .align, .balign, \
+                         .macro, .rept, .if, .include",
+                    ),
+            ),
         }
-        "j" => {
-            if args.len() != 1 {
-                return Err(format!("line {line_no}: j expects 1 operand: j label"));
+        Ok(())
+    }
+
+    fn warn(&mut self, warning: AsmError) {
+        self.diagnostics.push((Severity::Warning, warning));
+    }
+
+    fn error(&mut self, error: AsmError) {
+        self.diagnostics.push((Severity::Error, error));
+    }
+
+    fn require_data(&self, directive: &str, line_no: usize) -> Result<(), AsmError> {
+        if self.section != Section::Data {
+            return Err(AsmError::new(
+                line_no,
+                "wrong-section",
+                format!("{directive} is only supported in .data"),
+            )
+            .token(directive)
+            .hint("add a '.data' line first"));
+        }
+        Ok(())
+    }
+
+    /// Append one `.word`/`.half`/`.byte` value; `.word` may also name a label.
+    fn emit_data_value(&mut self, line_no: usize, token: &str, width: usize) -> Result<(), AsmError> {
+        let cx = LineCx {
+            line_no,
+            consts: &self.consts,
+        };
+        let bits = 8 * width as u32;
+        let value = match parse_i64(token, &cx) {
+            Ok(v) => {
+                let (min, max) = (-(1i64 << (bits - 1)), (1i64 << bits) - 1);
+                if v < min || v > max {
+                    return Err(AsmError::new(
+                        line_no,
+                        "imm-range",
+                        format!("value '{token}' does not fit in {width} byte(s)"),
+                    )
+                    .token(token));
+                }
+                v
             }
-            PendingInstr::Jal {
-                rd: 0u8,
-                target: args[0].to_string(),
+            Err(_) if width == 4 && is_symbol_name(token) => {
+                self.data_fixups.push(DataFixup {
+                    line_no,
+                    offset: self.data.len(),
+                    target: token.to_string(),
+                });
+                0
             }
+            Err(e) => return Err(e),
+        };
+        self.data.extend_from_slice(&(value as u32).to_le_bytes()[..width]);
+        Ok(())
+    }
+
+    fn align(&mut self, align: usize, line_no: usize) {
+        match self.section {
+            Section::Data => {
+                let len = self.data.len().next_multiple_of(align);
+                self.data.resize(len, 0);
+            }
+            // Text is word-granular, so this pads with `nop`s; how many is only known once
+            // branch relaxation has settled the layout.
+            Section::Text => self.items.push(PendingLine {
+                line_no,
+                instr: PendingInstr::Align(align.max(4) as u32),
+            }),
         }
-        "li" => {
-            if args.len() != 2 {
-                return Err(format!("line {line_no}: li expects 2 operands: li rd, imm"));
+    }
+
+    /// Address of `target`; `text_only` rejects data labels (branch/jump targets).
+    fn symbol_addr(&self, layout: &Layout, target: &str, line_no: usize, text_only: bool) -> Result<i32, AsmError> {
+        match self.symbols.get(target) {
+            Some(&Symbol::Text(idx)) => Ok(layout.text_addr(idx) as i32),
+            Some(&Symbol::Data(_)) if text_only => Err(AsmError::new(
+                line_no,
+                "label-kind",
+                format!("'{target}' is a .data label; branch and jump targets must be in .text"),
+            )
+            .token(target)),
+            Some(&Symbol::Data(off)) => Ok(layout.data_base.wrapping_add(off as u32) as i32),
+            None => {
+                let err = AsmError::new(line_no, "undefined-label", format!("unknown label '{target}'")).token(target);
+                let closest = self
+                    .symbols
+                    .keys()
+                    .map(|name| (edit_distance(name, target), name))
+                    .filter(|&(d, _)| d <= 2)
+                    .min();
+                Err(match closest {
+                    Some((_, name)) => err.hint(format!("did you mean '{name}'?")),
+                    None => err,
+                })
             }
-            let rd = parse_reg(args[0], line_no)?;
-            let imm = parse_i32(args[1], line_no)?;
-            if !(-2048..=2047).contains(&imm) {
-                return Err(format!(
-                    "line {line_no}: li immediate out of range for MVP (-2048..2047). Use 'lui'/'addi' or paste raw words."
-                ));
+        }
+    }
+
+    /// Value of `reloc` for the word at `pc`.
+    fn reloc_value(&self, layout: &Layout, reloc: &Reloc, pc: i32, line_no: usize) -> Result<i32, AsmError> {
+        let symbol = &reloc.symbol;
+        let text_only = matches!(reloc.kind, RelocKind::Branch | RelocKind::Jal);
+        let addr = || -> Result<i32, AsmError> {
+            Ok(self.symbol_addr(layout, symbol, line_no, text_only)?.wrapping_add(reloc.addend))
+        };
+        Ok(match reloc.kind {
+            RelocKind::Branch => {
+                check_offset(addr()?.wrapping_sub(pc) as i64, B_OFFSET_RANGE, "branch", line_no, symbol)?
+            }
+            RelocKind::Jal => check_offset(addr()?.wrapping_sub(pc) as i64, J_OFFSET_RANGE, "jal", line_no, symbol)
+                .map_err(|e| e.hint("use `call`/`tail` to reach any address"))?,
+            RelocKind::Hi20 => split_hi_lo(addr()?).0,
+            RelocKind::Lo12 => split_hi_lo(addr()?).1,
+            RelocKind::PcrelHi20 => split_hi_lo(addr()?.wrapping_sub(pc)).0,
+            RelocKind::PcrelLo12Paired => split_hi_lo(addr()?.wrapping_sub(pc.wrapping_sub(4))).1,
+            RelocKind::PcrelLo12 => {
+                // The operand names the `auipc`; the offset comes from that instruction's `%pcrel_hi`.
+                let anchor = match self.symbols.get(symbol) {
+                    Some(&Symbol::Text(idx)) => idx,
+                    // Undefined or a data label: report it the way a jump target would be.
+                    _ => return Err(self.symbol_addr(layout, symbol, line_no, true).unwrap_err()),
+                };
+                let hi = match self.items.get(anchor).map(|item| &item.instr) {
+                    Some(PendingInstr::Reloc(words)) if words[0].reloc.kind == RelocKind::PcrelHi20 => {
+                        &words[0].reloc
+                    }
+                    _ => {
+                        return Err(AsmError::new(
+                            line_no,
+                            "pcrel-lo-anchor",
+                            format!("'{symbol}' does not label an `auipc` with %pcrel_hi"),
+                        )
+                        .token(symbol)
+                        .hint("%pcrel_lo takes the label of its auipc, not the target symbol"))
+                    }
+                };
+                let auipc_pc = layout.text_addr(anchor) as i32;
+                let target = self.symbol_addr(layout, &hi.symbol, line_no, false)?.wrapping_add(hi.addend);
+                split_hi_lo(target.wrapping_sub(auipc_pc)).1
+            }
+        })
+    }
+
+    /// Final instructions for text item `i`, with relocations applied.
+    fn resolve_item(&self, layout: &Layout, i: usize) -> Result<Vec<RiscvInstruction>, AsmError> {
+        let item = &self.items[i];
+        let pc_from = layout.text_addr(i) as i32;
+        let line_no = item.line_no;
+        Ok(match &item.instr {
+            PendingInstr::Resolved(instr) => vec![instr.clone()],
+            PendingInstr::Expanded(seq) => seq.clone(),
+            PendingInstr::Align(_) => vec![RiscvInstruction::Nop; layout.offsets[i + 1] - layout.offsets[i]],
+            PendingInstr::Reloc(words) if layout.relaxed[i] => {
+                // `b<cond> far` → `b<!cond> +8; jal x0, far`
+                let RiscvInstruction::Branch { cond, rs1, rs2, .. } = words[0].instr else {
+                    unreachable!("only branches are relaxed");
+                };
+                let jal = Reloc {
+                    kind: RelocKind::Jal,
+                    ..words[0].reloc.clone()
+                };
+                let imm = self.reloc_value(layout, &jal, pc_from.wrapping_add(4), line_no)?;
+                vec![
+                    RiscvInstruction::Branch {
+                        cond: invert_cond(cond),
+                        rs1,
+                        rs2,
+                        imm: 8,
+                    },
+                    RiscvInstruction::Jal { rd: 0, imm },
+                ]
+            }
+            PendingInstr::Reloc(words) => words
+                .iter()
+                .enumerate()
+                .map(|(k, w)| {
+                    let value = self.reloc_value(layout, &w.reloc, pc_from.wrapping_add(4 * k as i32), line_no)?;
+                    Ok(with_imm(&w.instr, value))
+                })
+                .collect::<Result<_, AsmError>>()?,
+        })
+    }
+
+    /// Warn about labels nothing refers to and about code that follows an `ecall` with no label
+    /// to jump to it.
+    fn lint(&mut self) {
+        let mut used: HashSet<&str> = self.globals.iter().map(String::as_str).collect();
+        used.insert("_start");
+        for item in &self.items {
+            if let PendingInstr::Reloc(words) = &item.instr {
+                used.extend(words.iter().map(|w| w.reloc.symbol.as_str()));
+            }
+        }
+        used.extend(self.data_fixups.iter().map(|f| f.target.as_str()));
+
+        let mut warnings = Vec::new();
+        for (name, &line_no) in &self.label_lines {
+            if !used.contains(name.as_str()) {
+                warnings.push(
+                    AsmError::new(line_no, "unused-label", format!("label '{name}' is never used"))
+                        .token(name)
+                        .hint("declare it with .globl to silence this"),
+                );
             }
-            PendingInstr::Resolved(RiscvInstruction::IAlu {
-                op: RiscvOpcode::Add,
-                rd,
-                rs1: 0,
-                imm,
-            })
         }
-        "mv" => {
-            if args.len() != 2 {
-                return Err(format!("line {line_no}: mv expects 2 operands: mv rd, rs"));
-            }
-            let rd = parse_reg(args[0], line_no)?;
-            let rs = parse_reg(args[1], line_no)?;
-            PendingInstr::Resolved(RiscvInstruction::IAlu {
-                op: RiscvOpcode::Add,
-                rd,
-                rs1: rs,
-                imm: 0,
+
+        let labeled: HashSet<usize> = self
+            .symbols
+            .values()
+            .filter_map(|sym| match *sym {
+                Symbol::Text(idx) => Some(idx),
+                Symbol::Data(_) => None,
             })
+            .collect();
+        // One warning per unreachable run: code stays dead from an `ecall` until the next label.
+        let (mut dead, mut reported) = (false, false);
+        for (i, item) in self.items.iter().enumerate() {
+            if labeled.contains(&i) {
+                (dead, reported) = (false, false);
+            }
+            if matches!(item.instr, PendingInstr::Align(_)) {
+                continue;
+            }
+            if dead && !reported {
+                warnings.push(
+                    AsmError::new(item.line_no, "unreachable-code", "unreachable code after ecall")
+                        .hint("add a label if this is a jump target"),
+                );
+                reported = true;
+            }
+            if matches!(item.instr, PendingInstr::Resolved(RiscvInstruction::Halt)) {
+                dead = true;
+            }
         }
-        "ecall" | "halt" => {
-            if !args.is_empty() {
-                return Err(format!("line {line_no}: {op} takes no operands"));
+
+        warnings.sort_by_key(|w| w.line_no);
+        for w in warnings {
+            self.warn(w);
+        }
+    }
+
+    /// Resolve labels and lay out text and data. Errors are recorded in `diagnostics`; the
+    /// program is only returned if there were none.
+    fn finish(&mut self, text_base: u32, data_base: u32) -> Option<Rv32Program> {
+        self.lint();
+
+        // Relax conditional branches whose label is out of B-type range into an inverted branch
+        // over a `jal`. Every pass lays the text out again, `.align` padding included, so an
+        // alignment stays exact after the branches before it grow; branches are only ever
+        // relaxed, never un-relaxed, so the loop ends.
+        let mut relaxed = vec![false; self.items.len()];
+        let layout = loop {
+            let layout = Layout::new(&self.items, relaxed.clone(), text_base, data_base);
+            let mut changed = false;
+            for (i, item) in self.items.iter().enumerate() {
+                let PendingInstr::Reloc(words) = &item.instr else {
+                    continue;
+                };
+                let reloc = &words[0].reloc;
+                if reloc.kind != RelocKind::Branch || relaxed[i] {
+                    continue;
+                }
+                if let Some(&Symbol::Text(idx)) = self.symbols.get(&reloc.symbol) {
+                    let offset = 4 * (layout.offsets[idx] as i64 - layout.offsets[i] as i64) + reloc.addend as i64;
+                    if !B_OFFSET_RANGE.contains(&offset) {
+                        relaxed[i] = true;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break layout;
+            }
+        };
+        let words = layout.offsets[self.items.len()];
+
+        let mut program: Vec<RiscvInstruction> = Vec::with_capacity(words);
+        let mut lines = Vec::with_capacity(words);
+        for i in 0..self.items.len() {
+            let size = layout.offsets[i + 1] - layout.offsets[i];
+            lines.extend(std::iter::repeat_n(self.items[i].line_no, size));
+            match self.resolve_item(&layout, i) {
+                Ok(seq) => program.extend(seq),
+                Err(e) => {
+                    // Keep the layout intact so later items still report correct addresses.
+                    self.error(e);
+                    program.extend(std::iter::repeat_n(RiscvInstruction::Nop, size));
+                }
             }
-            PendingInstr::Resolved(RiscvInstruction::Halt)
         }
-        "nop" => {
-            if !args.is_empty() {
-                return Err(format!("line {line_no}: nop takes no operands"));
+
+        for f in 0..self.data_fixups.len() {
+            let fixup = &self.data_fixups[f];
+            match self.symbol_addr(&layout, &fixup.target, fixup.line_no, false) {
+                Ok(addr) => {
+                    let offset = fixup.offset;
+                    self.data[offset..offset + 4].copy_from_slice(&(addr as u32).to_le_bytes());
+                }
+                Err(e) => self.error(e),
             }
-            PendingInstr::Resolved(RiscvInstruction::Nop)
         }
-        _ => {
-            return Err(format!(
-                "line {line_no}: unsupported opcode '{op}' (supported: addi, add, lw, sw, beq, jal, j, li, mv, ecall, nop)"
-            ))
+
+        if self
+            .diagnostics
+            .iter()
+            .any(|(severity, _)| *severity == Severity::Error)
+        {
+            return None;
         }
+
+        let mut data = std::mem::take(&mut self.data);
+        data.resize(data.len().next_multiple_of(4), 0);
+        let ram_init = data
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(i, c)| (data_base.wrapping_add(4 * i as u32), u32::from_le_bytes([c[0], c[1], c[2], c[3]])))
+            .filter(|&(_, value)| value != 0)
+            .collect();
+
+        let mut symbols: Vec<(String, u32)> = self
+            .symbols
+            .iter()
+            .map(|(name, sym)| {
+                let addr = match *sym {
+                    Symbol::Text(idx) => layout.text_addr(idx),
+                    Symbol::Data(off) => data_base.wrapping_add(off as u32),
+                };
+                (name.clone(), addr)
+            })
+            .collect();
+        symbols.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+
+        Some(Rv32Program {
+            rom: encode_program(&program),
+            ram_init,
+            symbols,
+            lines,
+        })
+    }
+}
+
+/// Resolved addresses: word offset of every text item, plus where `.text` and `.data` live.
+struct Layout {
+    /// `offsets[i]` is the word offset of item `i` (one extra entry for labels at end of text).
+    offsets: Vec<usize>,
+    /// Branch items expanded to an inverted branch + `jal`.
+    relaxed: Vec<bool>,
+    text_base: u32,
+    data_base: u32,
+}
+
+impl Layout {
+    fn new(items: &[PendingLine], relaxed: Vec<bool>, text_base: u32, data_base: u32) -> Self {
+        let mut offsets = Vec::with_capacity(items.len() + 1);
+        let mut words = 0usize;
+        for (item, &relax) in items.iter().zip(&relaxed) {
+            offsets.push(words);
+            words += item.instr.size_words(text_base.wrapping_add(4 * words as u32)) + relax as usize;
+        }
+        offsets.push(words);
+        Layout {
+            offsets,
+            relaxed,
+            text_base,
+            data_base,
+        }
+    }
+
+    /// Address of text item `idx`.
+    fn text_addr(&self, idx: usize) -> u32 {
+        self.text_base.wrapping_add(4 * self.offsets[idx] as u32)
+    }
+}
+
+fn is_symbol_name(token: &str) -> bool {
+    let mut chars = token.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$')
+}
+
+/// Nesting limit for macro expansion, `.rept` and `.include`; also stops runaway recursion.
+const MAX_EXPANSION_DEPTH: usize = 32;
+/// Cap on statements produced by preprocessing, so a huge `.rept` count fails instead of hanging.
+const MAX_EXPANDED_LINES: usize = 1 << 20;
+
+/// One statement after preprocessing. `line_no` is the top-level source line it came from (the
+/// `.include` or macro invocation for expanded text); `origin` locates it inside the expansion.
+#[derive(Clone)]
+struct SourceLine {
+    line_no: usize,
+    text: String,
+    origin: Option<String>,
+}
+
+impl SourceLine {
+    /// A line produced by expanding `self`, tagged with `origin` inside it. Direct recursion
+    /// (a macro expanding itself) is named once rather than once per level.
+    fn expanded(&self, text: String, origin: &str) -> SourceLine {
+        SourceLine {
+            line_no: self.line_no,
+            text,
+            origin: Some(match &self.origin {
+                Some(outer) if outer.rsplit(", ").next() == Some(origin) => outer.clone(),
+                Some(outer) => format!("{outer}, {origin}"),
+                None => origin.to_string(),
+            }),
+        }
+    }
+}
+
+/// Split a statement into `(label, directive/mnemonic, operands)`.
+fn split_statement(text: &str) -> (Option<&str>, &str, &str) {
+    let code = strip_comment(text).trim();
+    let (label, stmt) = match code.split_once(':') {
+        Some((label, after)) => (Some(label.trim()).filter(|l| !l.is_empty()), after.trim()),
+        None => (None, code),
     };
+    let (head, operands) = stmt.split_once(char::is_whitespace).unwrap_or((stmt, ""));
+    (label, head, operands.trim())
+}
 
-    out.push(PendingLine {
-        line_no,
-        instr: pending,
-    });
-    Ok(())
+struct Macro {
+    /// Parameter names with their optional defaults.
+    params: Vec<(String, Option<String>)>,
+    body: Vec<String>,
+}
+
+/// An open `.if` block.
+struct Cond {
+    line_no: usize,
+    /// Whether the code around the block is being assembled.
+    outer: bool,
+    taken: bool,
+    in_else: bool,
+}
+
+impl Cond {
+    fn active(&self) -> bool {
+        self.outer && self.taken != self.in_else
+    }
 }
 
-pub fn assemble_rv32_mini_asm(text: &str) -> Result<Vec<u8>, String> {
-    let mut labels: HashMap<String, usize> = HashMap::new();
-    let mut pending: Vec<PendingLine> = Vec::new();
-
-    for (idx, line) in text.lines().enumerate() {
-        let line_no = idx + 1;
-        parse_line(line_no, line, &mut labels, &mut pending)?;
-    }
-
-    let mut program: Vec<RiscvInstruction> = Vec::with_capacity(pending.len());
-    for (i, item) in pending.into_iter().enumerate() {
-        let pc_from = (i as i32) * 4;
-        let resolved = match item.instr {
-            PendingInstr::Resolved(instr) => instr,
-            PendingInstr::Beq { rs1, rs2, target } => {
-                let idx = labels
-                    .get(&target)
-                    .copied()
-                    .ok_or_else(|| format!("line {}: unknown label '{target}'", item.line_no))?;
-                let pc_to = (idx as i32) * 4;
-                let imm = pc_to - pc_from;
-                RiscvInstruction::Branch {
-                    cond: BranchCondition::Eq,
-                    rs1,
-                    rs2,
-                    imm,
+/// Replace `\param` with its argument, `\@` with the invocation number and `\()` with nothing.
+fn substitute_macro_args(text: &str, args: &[(String, String)], invocation: usize) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('\\') {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        if let Some(tail) = after.strip_prefix('@') {
+            out.push_str(&invocation.to_string());
+            rest = tail;
+        } else if let Some(tail) = after.strip_prefix("()") {
+            rest = tail;
+        } else {
+            let len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let name = &after[..len];
+            let (_, value) = args.iter().find(|(p, _)| p == name).ok_or_else(|| name.to_string())?;
+            out.push_str(value);
+            rest = &after[len..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expands `.include`, `.macro`, `.rept` and `.if`/`.else`/`.endif` into plain statements ahead
+/// of `Assembler::parse_line`.
+struct Preprocessor<'a> {
+    includes: &'a HashMap<String, String>,
+    macros: HashMap<String, Macro>,
+    /// `.equ` constants seen so far, for `.if` conditions and `.rept` counts.
+    consts: HashMap<String, i64>,
+    /// Expansion counter substituted for `\@`.
+    invocations: usize,
+    /// Files currently being included, to reject cycles.
+    include_stack: Vec<String>,
+    out: Vec<SourceLine>,
+    errors: Vec<AsmError>,
+    truncated: bool,
+}
+
+impl<'a> Preprocessor<'a> {
+    fn new(includes: &'a HashMap<String, String>, consts: HashMap<String, i64>) -> Self {
+        Preprocessor {
+            includes,
+            macros: HashMap::new(),
+            consts,
+            invocations: 0,
+            include_stack: Vec::new(),
+            out: Vec::new(),
+            errors: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    fn error(&mut self, err: AsmError, line: &SourceLine) {
+        self.errors.push(err.origin(line.origin.as_deref()));
+    }
+
+    fn emit(&mut self, line: SourceLine) {
+        if self.out.len() >= MAX_EXPANDED_LINES {
+            if !self.truncated {
+                self.truncated = true;
+                let err = AsmError::new(
+                    line.line_no,
+                    "expansion-limit",
+                    format!("preprocessing produced more than {MAX_EXPANDED_LINES} statements"),
+                )
+                .hint("check .rept counts and recursive macros");
+                self.error(err, &line);
+            }
+            return;
+        }
+        self.out.push(line);
+    }
+
+    fn process(&mut self, lines: &[SourceLine], depth: usize) {
+        let mut conds: Vec<Cond> = Vec::new();
+        let mut i = 0;
+        while i < lines.len() && !self.truncated {
+            let line = &lines[i];
+            i += 1;
+            let line_no = line.line_no;
+            let (label, head, operands) = split_statement(&line.text);
+            let head = head.to_ascii_lowercase();
+            let active = conds.last().is_none_or(Cond::active);
+
+            match head.as_str() {
+                ".if" | ".ifdef" | ".ifndef" => {
+                    let taken = active
+                        && self.condition(&head, operands, line_no).unwrap_or_else(|e| {
+                            self.error(e, line);
+                            false
+                        });
+                    conds.push(Cond {
+                        line_no,
+                        outer: active,
+                        taken,
+                        in_else: false,
+                    });
+                    continue;
+                }
+                ".else" => {
+                    match conds.last_mut() {
+                        Some(cond) if !cond.in_else => cond.in_else = true,
+                        Some(_) => self.error(AsmError::new(line_no, "bad-conditional", "duplicate .else"), line),
+                        None => self.error(AsmError::new(line_no, "bad-conditional", ".else without .if"), line),
+                    }
+                    continue;
                 }
+                ".endif" => {
+                    if conds.pop().is_none() {
+                        self.error(AsmError::new(line_no, "bad-conditional", ".endif without .if"), line);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            if !active {
+                continue;
             }
-            PendingInstr::Jal { rd, target } => {
-                let idx = labels
-                    .get(&target)
-                    .copied()
-                    .ok_or_else(|| format!("line {}: unknown label '{target}'", item.line_no))?;
-                let pc_to = (idx as i32) * 4;
-                let imm = pc_to - pc_from;
-                RiscvInstruction::Jal { rd, imm }
+
+            let expands = matches!(head.as_str(), ".macro" | ".rept" | ".include") || self.macros.contains_key(&head);
+            if let (true, Some(label)) = (expands, label) {
+                self.emit(SourceLine {
+                    line_no,
+                    text: format!("{label}:"),
+                    origin: line.origin.clone(),
+                });
+            }
+            if expands && depth >= MAX_EXPANSION_DEPTH {
+                let err = AsmError::new(
+                    line_no,
+                    "expansion-depth",
+                    format!("{head} nested more than {MAX_EXPANSION_DEPTH} levels deep"),
+                )
+                .token(&head)
+                .hint("check for a macro or include that expands itself");
+                self.error(err, line);
+                continue;
+            }
+
+            match head.as_str() {
+                ".macro" => {
+                    if let Some(body) = self.collect_block(lines, &mut i, ".macro", ".endm", line) {
+                        if let Err(e) = self.define_macro(operands, body, line_no) {
+                            self.error(e, line);
+                        }
+                    }
+                }
+                ".rept" => {
+                    let Some(body) = self.collect_block(lines, &mut i, ".rept", ".endr", line) else {
+                        continue;
+                    };
+                    let cx = LineCx {
+                        line_no,
+                        consts: &self.consts,
+                    };
+                    match parse_imm_in(operands, &cx, 0, 1 << 16, ".rept count") {
+                        Ok(count) => {
+                            let body: Vec<SourceLine> =
+                                body.into_iter().map(|text| line.expanded(text, ".rept")).collect();
+                            for _ in 0..count {
+                                self.process(&body, depth + 1);
+                            }
+                        }
+                        Err(e) => self.error(e, line),
+                    }
+                }
+                ".endm" | ".endr" => {
+                    let open = if head == ".endm" { ".macro" } else { ".rept" };
+                    self.error(
+                        AsmError::new(line_no, "bad-directive", format!("{head} without {open}")).token(&head),
+                        line,
+                    );
+                }
+                ".include" => {
+                    if let Err(e) = self.include(operands, line, depth) {
+                        self.error(e, line);
+                    }
+                }
+                _ if expands => {
+                    if let Err(e) = self.expand_macro(&head, operands, line, depth) {
+                        self.error(e, line);
+                    }
+                }
+                ".equ" | ".set" => {
+                    // Track constants for `.if`/`.rept`; the assembler pass still defines them.
+                    let args = parse_operands(operands);
+                    let cx = LineCx {
+                        line_no,
+                        consts: &self.consts,
+                    };
+                    if let [name, value] = args[..] {
+                        if let Ok(value) = parse_i64(value, &cx) {
+                            self.consts.insert(name.to_string(), value);
+                        }
+                    }
+                    self.emit(line.clone());
+                }
+                _ => self.emit(line.clone()),
             }
+        }
+
+        for cond in conds {
+            self.errors.push(
+                AsmError::new(cond.line_no, "bad-conditional", ".if without .endif").hint("close the block with .endif"),
+            );
+        }
+    }
+
+    /// Lines after `lines[*i - 1]` up to its matching `close`, skipping nested `open`/`close`
+    /// pairs; `*i` moves past the `close`.
+    fn collect_block(
+        &mut self,
+        lines: &[SourceLine],
+        i: &mut usize,
+        open: &str,
+        close: &str,
+        start: &SourceLine,
+    ) -> Option<Vec<String>> {
+        let mut nesting = 0usize;
+        for (k, line) in lines.iter().enumerate().skip(*i) {
+            let head = split_statement(&line.text).1.to_ascii_lowercase();
+            if head == open {
+                nesting += 1;
+            } else if head == close {
+                if nesting == 0 {
+                    let body = lines[*i..k].iter().map(|l| l.text.clone()).collect();
+                    *i = k + 1;
+                    return Some(body);
+                }
+                nesting -= 1;
+            }
+        }
+        *i = lines.len();
+        self.error(
+            AsmError::new(start.line_no, "bad-directive", format!("{open} without {close}"))
+                .token(open)
+                .hint(format!("close the block with {close}")),
+            start,
+        );
+        None
+    }
+
+    /// `.if value`, `.if a <op> b` (`==`, `!=`, `<`, `<=`, `>`, `>=`), `.ifdef NAME`, `.ifndef NAME`.
+    fn condition(&self, head: &str, operands: &str, line_no: usize) -> Result<bool, AsmError> {
+        if head != ".if" {
+            let name = operands.trim();
+            if !is_symbol_name(name) {
+                return Err(AsmError::new(line_no, "bad-conditional", format!("{head} expects a constant name")));
+            }
+            return Ok(self.consts.contains_key(name) == (head == ".ifdef"));
+        }
+        let cx = LineCx {
+            line_no,
+            consts: &self.consts,
+        };
+        let value = |token: &str| {
+            parse_i64(token, &cx).map_err(|e| e.hint(".if only sees numbers and .equ constants defined above it"))
         };
-        program.push(resolved);
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if let Some((lhs, rhs)) = operands.split_once(op) {
+                let (lhs, rhs) = (value(lhs)?, value(rhs)?);
+                return Ok(match op {
+                    "==" => lhs == rhs,
+                    "!=" => lhs != rhs,
+                    "<=" => lhs <= rhs,
+                    ">=" => lhs >= rhs,
+                    "<" => lhs < rhs,
+                    _ => lhs > rhs,
+                });
+            }
+        }
+        Ok(value(operands)? != 0)
     }
 
-    Ok(encode_program(&program))
+    /// `.macro name [param[=default]]...` with parameters separated by commas or spaces.
+    fn define_macro(&mut self, header: &str, body: Vec<String>, line_no: usize) -> Result<(), AsmError> {
+        let mut words = header.split(|c: char| c == ',' || c.is_whitespace()).filter(|w| !w.is_empty());
+        let name = words
+            .next()
+            .ok_or_else(|| AsmError::new(line_no, "bad-macro", ".macro expects a name"))?
+            .to_ascii_lowercase();
+        if !is_symbol_name(&name) {
+            return Err(AsmError::new(line_no, "bad-macro", format!("invalid macro name '{name}'")).token(&name));
+        }
+        let mut params: Vec<(String, Option<String>)> = Vec::new();
+        for word in words {
+            let (param, default) = match word.split_once('=') {
+                Some((param, default)) => (param, Some(default.to_string())),
+                None => (word, None),
+            };
+            if !is_symbol_name(param) || params.iter().any(|(p, _)| p == param) {
+                return Err(
+                    AsmError::new(line_no, "bad-macro", format!("invalid or repeated macro parameter '{param}'"))
+                        .token(param),
+                );
+            }
+            params.push((param.to_string(), default));
+        }
+        if self.macros.contains_key(&name) {
+            return Err(AsmError::new(line_no, "duplicate-macro", format!("macro '{name}' is already defined"))
+                .token(&name));
+        }
+        self.macros.insert(name, Macro { params, body });
+        Ok(())
+    }
+
+    fn expand_macro(&mut self, name: &str, operands: &str, line: &SourceLine, depth: usize) -> Result<(), AsmError> {
+        let line_no = line.line_no;
+        let mac = &self.macros[name];
+        let args = parse_operands(operands);
+        if args.len() > mac.params.len() {
+            return Err(AsmError::new(
+                line_no,
+                "operand-count",
+                format!("macro '{name}' takes {} argument(s), got {}", mac.params.len(), args.len()),
+            ));
+        }
+        let mut values = Vec::with_capacity(mac.params.len());
+        for (k, (param, default)) in mac.params.iter().enumerate() {
+            let value = match (args.get(k), default) {
+                (Some(arg), _) => arg.to_string(),
+                (None, Some(default)) => default.clone(),
+                (None, None) => {
+                    return Err(AsmError::new(
+                        line_no,
+                        "operand-count",
+                        format!("macro '{name}' is missing argument '{param}'"),
+                    ))
+                }
+            };
+            values.push((param.clone(), value));
+        }
+
+        self.invocations += 1;
+        let origin = format!("macro '{name}'");
+        let body = mac
+            .body
+            .iter()
+            .map(|text| {
+                let text = substitute_macro_args(text, &values, self.invocations).map_err(|param| {
+                    AsmError::new(line_no, "bad-macro", format!("unknown macro parameter '\\{param}' in '{name}'"))
+                })?;
+                Ok(line.expanded(text, &origin))
+            })
+            .collect::<Result<Vec<_>, AsmError>>()?;
+        self.process(&body, depth + 1);
+        Ok(())
+    }
+
+    /// `.include "name"`, looked up in the caller-provided virtual files.
+    fn include(&mut self, operands: &str, line: &SourceLine, depth: usize) -> Result<(), AsmError> {
+        let line_no = line.line_no;
+        let name = operands.trim();
+        let name = name
+            .strip_prefix('"')
+            .and_then(|n| n.strip_suffix('"'))
+            .unwrap_or(name);
+        let Some(source) = self.includes.get(name) else {
+            return Err(AsmError::new(line_no, "missing-include", format!("no include file named '{name}'"))
+                .token(name)
+                .hint("pass its contents in the `includes` map"));
+        };
+        if self.include_stack.iter().any(|open| open == name) {
+            return Err(AsmError::new(line_no, "include-cycle", format!("'{name}' includes itself")).token(name));
+        }
+        let lines: Vec<SourceLine> = source
+            .lines()
+            .enumerate()
+            .map(|(k, text)| line.expanded(text.to_string(), &format!("{name}:{}", k + 1)))
+            .collect();
+        self.include_stack.push(name.to_string());
+        self.process(&lines, depth + 1);
+        self.include_stack.pop();
+        Ok(())
+    }
+}
+
+/// Assembly result: the program (absent if there were errors) and every diagnostic, sorted
+/// by line.
+#[derive(Debug, Clone)]
+pub struct Rv32AsmOutput {
+    pub program: Option<Rv32Program>,
+    pub diagnostics: Vec<AsmDiagnostic>,
+}
+
+/// Assemble mini-asm, recovering from errors so the whole file is checked in one pass: a line
+/// that fails to parse is reported and skipped, and unresolved labels are reported per use.
+///
+/// `.include "name"` reads `includes[name]`. Macros, `.rept` and includes are expanded first;
+/// expanded statements report the line of the invocation, with the macro/file position appended.
+/// `layout` places `.text`/`.data`, predefines its `symbols` as constants and optionally links
+/// an `ecall` shim.
+pub fn assemble_rv32(text: &str, includes: &HashMap<String, String>, layout: &Rv32AsmLayout) -> Rv32AsmOutput {
+    let consts: HashMap<String, i64> = layout.symbols.iter().cloned().collect();
+    let mut pre = Preprocessor::new(includes, consts.clone());
+    let top: Vec<SourceLine> = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| SourceLine {
+            line_no: idx + 1,
+            text: line.to_string(),
+            origin: None,
+        })
+        .collect();
+    pre.process(&top, 0);
+    if let Some(shim) = &layout.ecall_shim {
+        let lines: Vec<SourceLine> = std::iter::once(".text")
+            .chain(shim.source.lines())
+            .map(|text| SourceLine {
+                line_no: 0,
+                text: text.to_string(),
+                origin: Some("ecall shim".into()),
+            })
+            .collect();
+        pre.process(&lines, 0);
+    }
+
+    let mut asm = Assembler {
+        predefined: consts.keys().cloned().collect(),
+        consts,
+        ecall_shim: layout.ecall_shim.as_ref().map(|shim| (shim.entry, shim.t6_slot)),
+        ..Assembler::default()
+    };
+    for e in pre.errors {
+        asm.error(e);
+    }
+    for line in &pre.out {
+        if let Err(e) = asm.parse_line(line.line_no, &line.text) {
+            asm.error(e.origin(line.origin.as_deref()));
+        }
+    }
+    let program = asm.finish(layout.text_base, layout.data_base);
+
+    let source_lines: Vec<&str> = text.lines().collect();
+    let mut diagnostics: Vec<AsmDiagnostic> = asm
+        .diagnostics
+        .into_iter()
+        .map(|(severity, e)| AsmDiagnostic::locate(e, severity, &source_lines))
+        .collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column_start));
+    Rv32AsmOutput { program, diagnostics }
+}
+
+/// Assemble mini-asm into a ROM image (`.text` at `layout.text_base`) and RAM initialization
+/// (`.data` at `layout.data_base`). Errors are joined one per line, and the error carries the
+/// first one's line; warnings are dropped.
+pub fn assemble_rv32_mini_asm(
+    text: &str,
+    includes: &HashMap<String, String>,
+    layout: &Rv32AsmLayout,
+) -> Result<Rv32Program, NeoDemoError> {
+    let out = assemble_rv32(text, includes, layout);
+    out.program.ok_or_else(|| {
+        let errors: Vec<&AsmDiagnostic> = out.diagnostics.iter().filter(|d| d.severity == Severity::Error).collect();
+        let message = errors.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n");
+        match errors.first().map(|d| d.line).filter(|&line| line > 0) {
+            Some(line) => NeoDemoError::assemble(message).at_line(line),
+            None => NeoDemoError::assemble(message),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rv32_disasm::disassemble_word;
+    use crate::rv32_exec::{Rv32HaltReason, Rv32Machine};
+    use deprecated_neo_memory::riscv::lookups::decode_instruction;
+
+    const TEXT_BASE: u32 = 0x1000;
+
+    fn assemble(src: &str) -> Rv32AsmOutput {
+        let layout = Rv32AsmLayout {
+            text_base: TEXT_BASE,
+            ..Default::default()
+        };
+        assemble_rv32(src, &HashMap::new(), &layout)
+    }
+
+    fn program(src: &str) -> Rv32Program {
+        let out = assemble(src);
+        out.program.unwrap_or_else(|| panic!("{src:?} failed: {:?}", out.diagnostics))
+    }
+
+    fn words(p: &Rv32Program) -> Vec<u32> {
+        p.rom.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+    }
+
+    fn error_code(src: &str) -> &'static str {
+        let out = assemble(src);
+        assert!(out.program.is_none(), "{src:?} should not assemble");
+        out.diagnostics.iter().find(|d| d.severity == Severity::Error).unwrap().code
+    }
+
+    fn symbol(p: &Rv32Program, name: &str) -> u32 {
+        p.symbols.iter().find(|(n, _)| n == name).unwrap().1
+    }
+
+    fn run(p: &Rv32Program) -> Rv32Machine {
+        let mut machine = Rv32Machine::new(TEXT_BASE, &p.rom, &p.ram_init).unwrap();
+        machine.run(100_000);
+        assert_eq!(machine.halted, Some(Rv32HaltReason::Ecall));
+        machine
+    }
+
+    #[test]
+    fn every_diagnostic_is_reported_with_its_span() {
+        let src = "\
+.globl start
+start:
+    addi a0, a1, 5000
+    add a0, a9, a1   # bad register
+    frob a0
+unused:
+    ecall
+    nop
+";
+        let out = assemble(src);
+        assert!(out.program.is_none());
+        let found: Vec<_> = out
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code, d.line, d.column_start, d.column_end))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Error, "imm-range", 3, 18, 22),
+                (Severity::Error, "bad-register", 4, 13, 15),
+                (Severity::Error, "unknown-opcode", 5, 5, 9),
+                (Severity::Warning, "unused-label", 6, 1, 7),
+                (Severity::Warning, "unreachable-code", 8, 5, 8),
+            ]
+        );
+        assert!(out.diagnostics[1].hint.is_some());
+
+        // Warnings alone don't stop assembly.
+        let out = assemble("unused:\n    ecall\n    nop\n");
+        assert!(out.program.is_some());
+        assert!(out.diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn instructions_round_trip_through_decode_and_disassembly() {
+        let lines = [
+            "add a0, a1, a2",
+            "sub t0, t1, t2",
+            "and s0, s1, a0",
+            "or a3, a4, a5",
+            "xor a6, a7, s2",
+            "sll t3, t4, t5",
+            "srl t6, zero, ra",
+            "sra sp, gp, tp",
+            "slt s3, s4, s5",
+            "sltu s6, s7, s8",
+            "mul a0, a1, a2",
+            "mulh a0, a1, a2",
+            "mulhu a0, a1, a2",
+            "mulhsu a0, a1, a2",
+            "div a0, a1, a2",
+            "divu a0, a1, a2",
+            "rem a0, a1, a2",
+            "remu a0, a1, a2",
+            "addi a0, a1, -2048",
+            "addi a0, a1, 2047",
+            "andi a0, a1, 255",
+            "ori a0, a1, -1",
+            "xori a0, a1, 1",
+            "slti a0, a1, -5",
+            "sltiu a0, a1, 5",
+            "slli a0, a1, 31",
+            "srli a0, a1, 1",
+            "srai a0, a1, 17",
+            "lb a0, -1(sp)",
+            "lh a0, 2(sp)",
+            "lw a0, 2047(sp)",
+            "lbu a0, -2048(sp)",
+            "lhu a0, 6(sp)",
+            "sb a0, -1(sp)",
+            "sh a0, 2(sp)",
+            "sw a0, 2047(sp)",
+            "beq a0, a1, -4096",
+            "bne a0, a1, 4094",
+            "blt a0, a1, 8",
+            "bge a0, a1, -8",
+            "bltu a0, a1, 2",
+            "bgeu a0, a1, -2",
+            "jal ra, -1048576",
+            "jal zero, 1048574",
+            "jalr ra, -12(t0)",
+            "lui a0, 0xfffff",
+            "auipc t1, 0x80000",
+            "nop",
+            "ecall",
+        ];
+        for line in lines {
+            let word = words(&program(line))[0];
+            decode_instruction(word).unwrap_or_else(|e| panic!("{line:?} -> {word:#010x} does not decode: {e}"));
+            let text = disassemble_word(word, TEXT_BASE);
+            assert_eq!(words(&program(&text)), [word], "{line:?} disassembled to {text:?}");
+        }
+    }
+
+    #[test]
+    fn immediates_and_offsets_at_their_limits() {
+        for line in ["addi a0, a0, -2048", "addi a0, a0, 2047", "sw a0, -2048(sp)", "lw a0, 2047(sp)"] {
+            program(line);
+        }
+        for line in ["addi a0, a0, -2049", "addi a0, a0, 2048", "sw a0, 2048(sp)", "lw a0, -2049(sp)"] {
+            assert_eq!(error_code(line), "imm-range", "{line}");
+        }
+
+        let cases = [
+            ("beq a0, a1, -4096", -4096),
+            ("beq a0, a1, 4094", 4094),
+            ("jal ra, -1048576", -1048576),
+            ("jal ra, 1048574", 1048574),
+        ];
+        for (line, imm) in cases {
+            match decode_instruction(words(&program(line))[0]).unwrap() {
+                RiscvInstruction::Branch { imm: got, .. } | RiscvInstruction::Jal { imm: got, .. } => {
+                    assert_eq!(got, imm, "{line}")
+                }
+                other => panic!("{line} decoded to {other:?}"),
+            }
+        }
+        for line in ["beq a0, a1, -4098", "beq a0, a1, 4096", "jal ra, -1048578", "jal ra, 1048576"] {
+            assert_eq!(error_code(line), "offset-range", "{line}");
+        }
+        assert_eq!(error_code("beq a0, a1, 3"), "misaligned-offset");
+        assert_eq!(error_code("jal ra, -1"), "misaligned-offset");
+    }
+
+    #[test]
+    fn li_splits_across_the_lo12_carry() {
+        let values: [i64; 12] = [
+            0,
+            2047,
+            -2048,
+            2048,
+            -2049,
+            0x7ff,
+            0x800,
+            0xfff,
+            0x1800,
+            0x7fff_f800,
+            0x1234_5fff,
+            0xffff_f800,
+        ];
+        for value in values.into_iter().chain([i32::MIN as i64, i32::MAX as i64, 0xffff_ffff]) {
+            let p = program(&format!("li a0, {value}\necall"));
+            let machine = run(&p);
+            assert_eq!(machine.regs[10], value as u32, "li a0, {value:#x}");
+            let max_words = if (-2048..2048).contains(&(value as u32 as i32)) { 1 } else { 2 };
+            assert!(words(&p).len() - 1 <= max_words, "li a0, {value:#x} is too long");
+        }
+    }
+
+    #[test]
+    fn pseudo_instructions_expand_and_run() {
+        let p = program(
+            "
+_start:
+    la   a0, table
+    lw   a1, 4(a0)
+    mv   a2, a1
+    not  a3, a2
+    neg  a4, a2
+    seqz a5, zero
+    snez a6, a2
+    call double
+    li   s0, 0
+    bgt  a2, a1, over
+    li   s0, 99
+over:
+    beqz s0, done
+    li   s1, 1
+done:
+    j    end
+double:
+    add  a2, a2, a2
+    ret
+end:
+    ecall
+.data
+table: .word 10, 20
+",
+        );
+        let w = words(&p);
+        // `la` and `call` are `auipc` pairs, whatever the distance to their target.
+        let decoded = |i: usize| decode_instruction(w[i]).unwrap();
+        assert!(matches!(decoded(0), RiscvInstruction::Auipc { rd: 10, .. }), "{:?}", decoded(0));
+        assert!(matches!(decoded(1), RiscvInstruction::IAlu { op: RiscvOpcode::Add, rd: 10, rs1: 10, .. }));
+        assert!(matches!(decoded(8), RiscvInstruction::Auipc { rd: 1, .. }), "{:?}", decoded(8));
+        assert!(matches!(decoded(9), RiscvInstruction::Jalr { rd: 1, rs1: 1, .. }), "{:?}", decoded(9));
+
+        let regs = run(&p).regs;
+        assert_eq!(regs[10], DEFAULT_DATA_BASE);
+        assert_eq!(regs[11..=16], [20, 40, !20, 20u32.wrapping_neg(), 1, 1]);
+        assert_eq!((regs[8], regs[9]), (0, 0));
+    }
+
+    #[test]
+    fn far_branches_are_relaxed() {
+        let nops = "    nop\n".repeat(1100);
+        let p = program(&format!(
+            "_start:\n    li a0, 1\n    beq a0, a0, far\n    li a1, 2\n    ecall\n{nops}far:\n    li a1, 3\n    ecall\n"
+        ));
+        // `beq` out of range becomes `bne a0, a0, +8` over a `jal far`.
+        assert_eq!(words(&p).len(), 1 + 2 + 2 + 1100 + 2);
+        assert_eq!(run(&p).regs[11], 3);
+
+        let near = program("_start:\n    beq a0, a0, done\n    nop\ndone:\n    ecall\n");
+        assert_eq!(words(&near).len(), 3);
+    }
+
+    #[test]
+    fn align_padding_follows_relaxation() {
+        let nops = "    nop\n".repeat(1200);
+        let p = program(&format!("_start:\n    beq a0, a1, far\n    .align 4\naligned:\n    nop\n{nops}far:\n    ecall\n"));
+        assert_eq!(symbol(&p, "aligned") % 16, 0);
+        assert_eq!(symbol(&p, "aligned") - TEXT_BASE, 16);
+    }
+
+    #[test]
+    fn duplicate_labels_are_rejected() {
+        assert_eq!(error_code("a:\n    nop\na:\n    ecall\n"), "duplicate-label");
+        assert_eq!(error_code("a:\n    nop\n.data\na: .word 1\n"), "duplicate-label");
+    }
+
+    #[test]
+    fn labels_on_every_branch_and_jump_form() {
+        for op in ["beq a0, a1,", "bne a0, a1,", "blt a0, a1,", "bge a0, a1,", "bltu a0, a1,", "bgeu a0, a1,"] {
+            let p = program(&format!("back:\n    nop\n    {op} back\n    {op} fwd\n    nop\nfwd:\n    ecall\n"));
+            let imms: Vec<i32> = words(&p)[1..3]
+                .iter()
+                .map(|&w| match decode_instruction(w).unwrap() {
+                    RiscvInstruction::Branch { imm, .. } => imm,
+                    other => panic!("{op} decoded to {other:?}"),
+                })
+                .collect();
+            assert_eq!(imms, [-4, 8], "{op}");
+        }
+        for (src, rd) in [("jal ra, fwd", 1), ("jal fwd", 1), ("j fwd", 0), ("jal t0, fwd", 5)] {
+            let p = program(&format!("    {src}\n    nop\nfwd:\n    ecall\n"));
+            match decode_instruction(words(&p)[0]).unwrap() {
+                RiscvInstruction::Jal { rd: got, imm } => assert_eq!((got, imm), (rd, 8), "{src}"),
+                other => panic!("{src} decoded to {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn equ_constants_are_not_branch_targets() {
+        for src in [".equ T, 8\n    beq a0, a1, T\n", ".equ T, 8\n    j T\n", ".equ T, 8\n    call T\n"] {
+            assert_eq!(error_code(src), "const-target", "{src}");
+        }
+    }
+
+    #[test]
+    fn syscalls_preserve_registers_and_reserve_names() {
+        use crate::rv32_syscall::{Rv32Io, Rv32SyscallConfig, Rv32SyscallShim, DEFAULT_IO_BASE};
+
+        let config = Rv32SyscallConfig {
+            input: Vec::new(),
+            journal_words: 4,
+        };
+        let io = Rv32Io::new(DEFAULT_IO_BASE as u64, &config);
+        let layout = Rv32AsmLayout {
+            text_base: TEXT_BASE,
+            symbols: io.symbols(),
+            ecall_shim: Some(Rv32Io::shim()),
+            ..Default::default()
+        };
+        let src = "
+            li t6, 77
+            li a1, 55
+            li a0, 9
+            li a7, SYS_COMMIT
+            ecall
+            li a7, SYS_PRINT
+            ecall
+            add a0, t6, a1
+            li a7, SYS_HALT
+            ecall
+        ";
+        let out = assemble_rv32(src, &HashMap::new(), &layout);
+        let p = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        let ram = io.base..io.base + 4 * io.words() as u64;
+        let mut machine = Rv32Machine::new(TEXT_BASE, &p.rom, &io.init(&config.input))
+            .unwrap()
+            .with_ram_regions(Some(vec![ram]))
+            .with_syscalls(Rv32SyscallShim::locate(io, &p));
+        machine.run(10_000);
+        let report = machine.io_report().unwrap();
+        assert_eq!(report.journal, [9]);
+        assert_eq!(report.prints[0].value, 9);
+        assert_eq!(report.exit_code, 77 + 55);
+
+        for src in ["__neo_io:\n    nop\n", ".equ SYS_READ, 7\n", ".set __neo_tmp, 1\n"] {
+            let out = assemble_rv32(src, &HashMap::new(), &layout);
+            let errors: Vec<_> = out.diagnostics.iter().filter(|d| d.severity == Severity::Error).collect();
+            assert_eq!(errors.len(), 1, "{src}: {errors:?}");
+            assert_eq!(errors[0].code, "reserved-name", "{src}");
+        }
+    }
 }
@@ -0,0 +1,1387 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
+
+use js_sys::Date;
+use neo_fold::riscv_trace_shard::{Rv32TraceWiring, Rv32TraceWiringRun};
+use neo_math::F;
+use p3_field::PrimeCharacteristicRing;
+use sha2::{Digest, Sha256};
+use tsify::Tsify;
+
+use crate::error::NeoDemoError;
+use crate::riscv_asm::{Rv32AsmLayout, Rv32Program, DEFAULT_DATA_BASE};
+use crate::rv32_exec::{Rv32HaltReason, Rv32Machine, Rv32StepRecord};
+use crate::rv32_syscall::{Rv32Io, Rv32IoReport, Rv32SyscallConfig, Rv32SyscallShim, DEFAULT_IO_BASE, SAVE_AREA};
+use crate::spartan_keys::{circuit_digest, spartan_keys_id, SpartanKeys, RV32_SPARTAN_KEYS};
+use crate::{fold_run_witness_placeholder, hex_encode};
+
+/// One `ram_init_u32` entry: RAM[addr] = value before execution.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Tsify)]
+pub(crate) struct Rv32RamInit {
+    pub(crate) addr: u64,
+    pub(crate) value: u32,
+}
+
+/// Public output claim: RAM[addr] == expected once the guest halts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Tsify)]
+pub(crate) struct Rv32OutputClaim {
+    pub(crate) addr: u64,
+    pub(crate) expected: u32,
+}
+
+/// Guest input: `words` written to consecutive RAM words from `addr` before execution.
+///
+/// Public inputs are listed in the proof's `Rv32PublicStatement`; the others are not, and must
+/// not overlap anything the statement binds (see `check_unlisted`).
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct Rv32Input {
+    pub(crate) addr: u64,
+    pub(crate) words: Vec<u32>,
+    #[serde(default)]
+    pub(crate) public: bool,
+}
+
+/// Public output region: `words` consecutive RAM words from `addr`, claimed with whatever the
+/// guest left there (read from a native run before proving).
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct Rv32OutputRegion {
+    pub(crate) addr: u64,
+    pub(crate) words: usize,
+}
+
+/// What an RV32 proof attests: the program with digest `program_sha256`, loaded at
+/// `program_base` and started with the public `inputs` in RAM, halted with `outputs` in RAM.
+/// For guests built with `syscalls`, `outputs` also binds the exit code and journal, which are
+/// repeated decoded in `exit_code` and `journal`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, Tsify)]
+pub struct Rv32PublicStatement {
+    pub(crate) program_base: u64,
+    /// SHA-256 over the ROM bytes followed by the `.data` words (`addr`, `value`, little-endian).
+    pub(crate) program_sha256: String,
+    pub(crate) inputs: Vec<Rv32RamInit>,
+    pub(crate) outputs: Vec<Rv32OutputClaim>,
+    #[serde(default)]
+    #[tsify(optional)]
+    pub(crate) exit_code: Option<u32>,
+    #[serde(default)]
+    pub(crate) journal: Vec<u32>,
+}
+
+/// Hex SHA-256 identifying `program` (ROM image and `.data`) in a public statement.
+pub(crate) fn program_sha256(program: &Rv32Program) -> String {
+    let mut h = Sha256::new();
+    h.update(&program.rom);
+    for &(addr, value) in &program.ram_init {
+        h.update(addr.to_le_bytes());
+        h.update(value.to_le_bytes());
+    }
+    hex_encode(&h.finalize())
+}
+
+/// An address range in the guest's memory map.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Rv32Region {
+    pub(crate) base: u64,
+    pub(crate) size: u64,
+}
+
+impl Rv32Region {
+    fn end(&self) -> u64 {
+        self.base + self.size
+    }
+}
+
+/// Declared guest memory layout, as a linker script would describe it: code is fetched from
+/// `rom` (which starts at the program base), `.data` is placed at the start of `ram`, and
+/// loads/stores must land in `ram`, `stack` or `io`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Rv32MemoryMap {
+    pub(crate) rom: Rv32Region,
+    pub(crate) ram: Rv32Region,
+    #[serde(default)]
+    pub(crate) stack: Option<Rv32Region>,
+    #[serde(default)]
+    pub(crate) io: Option<Rv32Region>,
+}
+
+impl Rv32MemoryMap {
+    fn regions(&self) -> impl Iterator<Item = (&'static str, Rv32Region)> {
+        [
+            ("rom", Some(self.rom)),
+            ("ram", Some(self.ram)),
+            ("stack", self.stack),
+            ("io", self.io),
+        ]
+        .into_iter()
+        .filter_map(|(name, region)| Some((name, region?)))
+    }
+
+    fn validate(&self) -> Result<(), NeoDemoError> {
+        let regions: Vec<_> = self.regions().collect();
+        for (name, r) in &regions {
+            if r.base & 3 != 0 || r.size == 0 || r.size & 3 != 0 || r.end() > 1 << 32 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: memory_map.{name} (base {:#x}, size {:#x}) must be 4-byte aligned, non-empty and \
+                     inside the 32-bit address space",
+                    r.base, r.size
+                )));
+            }
+        }
+        for (i, (a, ra)) in regions.iter().enumerate() {
+            for (b, rb) in &regions[i + 1..] {
+                if ra.base < rb.end() && rb.base < ra.end() {
+                    return Err(NeoDemoError::parse(format!("config error: memory_map.{a} and memory_map.{b} overlap")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Assembler constants named like linker-script symbols: `__rom_start`, `__rom_end`, … for
+    /// every region, plus `__stack_top` (the end of `stack`).
+    fn symbols(&self) -> Vec<(String, i64)> {
+        let mut symbols: Vec<(String, i64)> = self
+            .regions()
+            .flat_map(|(name, r)| {
+                [
+                    (format!("__{name}_start"), r.base as i64),
+                    (format!("__{name}_end"), r.end() as i64),
+                ]
+            })
+            .collect();
+        if let Some(stack) = self.stack {
+            symbols.push(("__stack_top".into(), stack.end() as i64));
+        }
+        symbols
+    }
+}
+
+/// Where a guest lives in the address space: the program base and, optionally, a memory map
+/// and the syscall I/O block (see `rv32_syscall`).
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub(crate) struct Rv32Layout {
+    #[serde(default)]
+    pub(crate) program_base: Option<u64>,
+    #[serde(default)]
+    pub(crate) memory_map: Option<Rv32MemoryMap>,
+    #[serde(default)]
+    pub(crate) syscalls: Option<Rv32SyscallConfig>,
+}
+
+impl Rv32Layout {
+    /// `program_base`, else the start of `memory_map.rom`, else `0`.
+    pub(crate) fn program_base(&self) -> u64 {
+        self.program_base
+            .or(self.memory_map.as_ref().map(|map| map.rom.base))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), NeoDemoError> {
+        let base = self.program_base();
+        if base & 3 != 0 || base >= 1 << 32 {
+            return Err(NeoDemoError::parse(format!(
+                "config error: program_base {base:#x} is not a 4-byte aligned 32-bit address"
+            )));
+        }
+        if let Some(map) = &self.memory_map {
+            map.validate()?;
+            if base != map.rom.base {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: program_base {base:#x} differs from memory_map.rom.base {:#x}",
+                    map.rom.base
+                )));
+            }
+        }
+        if let Some(io) = self.io() {
+            if io.range().end > SAVE_AREA as u64 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: syscall I/O block at {:#x} ({} words) runs into the save area at {SAVE_AREA:#x}",
+                    io.base,
+                    io.words()
+                )));
+            }
+            if let Some(map) = &self.memory_map {
+                let block = io.range();
+                for (name, r) in map.regions() {
+                    if r.end() > SAVE_AREA as u64 {
+                        return Err(NeoDemoError::parse(format!(
+                            "config error: memory_map.{name} overlaps the syscall save area at {SAVE_AREA:#x}"
+                        )));
+                    }
+                    if name != "io" && r.base < block.end && block.start < r.end() {
+                        return Err(NeoDemoError::parse(format!(
+                            "config error: memory_map.{name} overlaps the syscall I/O block [{:#x}, {:#x})",
+                            block.start, block.end
+                        )));
+                    }
+                }
+                if let Some(region) = map.io.filter(|region| block.end > region.end()) {
+                    return Err(NeoDemoError::parse(format!(
+                        "config error: syscall I/O block ({} words) does not fit in memory_map.io (size {:#x})",
+                        io.words(),
+                        region.size
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The syscall I/O block, at `memory_map.io` (else `DEFAULT_IO_BASE`), when `syscalls` is set.
+    pub(crate) fn io(&self) -> Option<Rv32Io> {
+        let config = self.syscalls.as_ref()?;
+        let base = self
+            .memory_map
+            .as_ref()
+            .and_then(|map| map.io)
+            .map_or(DEFAULT_IO_BASE as u64, |io| io.base);
+        Some(Rv32Io::new(base, config))
+    }
+
+    /// Initial words of the syscall I/O block (header and input stream).
+    pub(crate) fn io_init(&self) -> Vec<Rv32RamInit> {
+        match (self.io(), &self.syscalls) {
+            (Some(io), Some(config)) => io
+                .init(&config.input)
+                .into_iter()
+                .map(|(addr, value)| Rv32RamInit {
+                    addr: addr as u64,
+                    value,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The syscall shim linked into `program`, for the interpreter to watch.
+    pub(crate) fn syscall_shim(&self, program: &Rv32Program) -> Option<Rv32SyscallShim> {
+        Rv32SyscallShim::locate(self.io()?, program)
+    }
+
+    /// Assembler placement: `.text` at the program base, `.data` at the start of
+    /// `memory_map.ram` (else `DEFAULT_DATA_BASE`), with the memory map's symbols predefined and,
+    /// with `syscalls`, the syscall shim linked.
+    pub(crate) fn asm_layout(&self) -> Rv32AsmLayout {
+        let io = self.io();
+        let mut symbols = self.memory_map.as_ref().map(Rv32MemoryMap::symbols).unwrap_or_default();
+        symbols.extend(io.iter().flat_map(Rv32Io::symbols));
+        Rv32AsmLayout {
+            text_base: self.program_base() as u32,
+            data_base: self
+                .memory_map
+                .as_ref()
+                .map_or(DEFAULT_DATA_BASE, |map| map.ram.base as u32),
+            symbols,
+            ecall_shim: io.map(|_| Rv32Io::shim()),
+        }
+    }
+
+    /// Address ranges loads/stores may touch, when a memory map declares them: its non-ROM
+    /// regions, plus the syscall I/O block when the map has no `io` region for it.
+    pub(crate) fn ram_regions(&self) -> Option<Vec<Range<u64>>> {
+        let map = self.memory_map.as_ref()?;
+        let mut regions: Vec<_> = map
+            .regions()
+            .filter(|&(name, _)| name != "rom")
+            .map(|(_, r)| r.base..r.end())
+            .collect();
+        if map.io.is_none() {
+            regions.extend(self.io().map(|io| io.range()));
+        }
+        Some(regions)
+    }
+
+    /// Error unless `program`'s ROM image fits in `memory_map.rom`.
+    pub(crate) fn check_program(&self, program: &Rv32Program) -> Result<(), NeoDemoError> {
+        match &self.memory_map {
+            Some(map) if program.rom.len() as u64 > map.rom.size => Err(NeoDemoError::shape(format!(
+                "memory map error: ROM image is {:#x} bytes, larger than memory_map.rom ({:#x} bytes)",
+                program.rom.len(),
+                map.rom.size
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn describe_regions(regions: &[Range<u64>]) -> String {
+    regions
+        .iter()
+        .map(|r| format!("[{:#x}, {:#x})", r.start, r.end))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Error unless `words` RAM words from `addr` fit in one of `regions`.
+fn check_in_regions(regions: &[Range<u64>], what: &str, addr: u64, words: usize) -> Result<(), String> {
+    let end = addr + 4 * words as u64;
+    if regions.iter().any(|r| r.start <= addr && end <= r.end) {
+        return Ok(());
+    }
+    Err(format!(
+        "{what} at {addr:#x} ({words} word(s)) lies outside RAM ({})",
+        describe_regions(regions)
+    ))
+}
+
+fn default_xlen() -> usize {
+    32
+}
+
+/// Configuration for `prove_verify_rv32_asm`, passed from JS as a plain object.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct Rv32ProveConfig {
+    /// Defaults to `memory_map.rom.base`, else `0`; see `Rv32Layout`.
+    #[serde(default)]
+    pub(crate) program_base: Option<u64>,
+    #[serde(default)]
+    pub(crate) memory_map: Option<Rv32MemoryMap>,
+    /// Enables the guest syscall ABI; assembled guests only.
+    #[serde(default)]
+    pub(crate) syscalls: Option<Rv32SyscallConfig>,
+    #[serde(default = "default_xlen")]
+    pub(crate) xlen: usize,
+    #[serde(default)]
+    pub(crate) ram_init: Vec<Rv32RamInit>,
+    /// Input words, applied over `ram_init`.
+    #[serde(default)]
+    pub(crate) inputs: Vec<Rv32Input>,
+    #[serde(default)]
+    pub(crate) outputs: Vec<Rv32OutputClaim>,
+    /// Output regions, expanded into `outputs` by `resolve`.
+    #[serde(default)]
+    pub(crate) output_regions: Vec<Rv32OutputRegion>,
+    pub(crate) chunk_rows: usize,
+    /// RAM size in bytes; every initialized word, output and guest load/store must fall inside
+    /// `[0, ram_bytes)`. Shorthand for a memory map with only RAM; absent (with no `memory_map`)
+    /// leaves the whole address space usable. Checked on the native run before proving only: the
+    /// prover is not told the bound, so the proof does not enforce it.
+    #[serde(default)]
+    pub(crate) ram_bytes: Option<u64>,
+    /// `0` keeps the prover default.
+    #[serde(default)]
+    pub(crate) max_steps: usize,
+    #[serde(default)]
+    pub(crate) do_spartan: bool,
+    /// Virtual files for `.include "name"` in assembled sources.
+    #[serde(default)]
+    pub(crate) includes: HashMap<String, String>,
+}
+
+/// Native execution budget used when the config leaves `max_steps` at `0`.
+pub(crate) const DEFAULT_EXEC_MAX_STEPS: usize = 1 << 20;
+
+impl Rv32ProveConfig {
+    pub(crate) fn validate(&self) -> Result<(), NeoDemoError> {
+        if self.xlen != 32 {
+            return Err(NeoDemoError::parse(format!(
+                "config error: unsupported xlen {} (only 32 is supported)",
+                self.xlen
+            )));
+        }
+        self.layout().validate()?;
+        if self.ram_bytes.is_some() && self.memory_map.is_some() {
+            return Err(NeoDemoError::parse("config error: set either ram_bytes or memory_map.ram, not both"));
+        }
+        if self.chunk_rows == 0 {
+            return Err(NeoDemoError::parse("config error: chunk_rows must be > 0"));
+        }
+        if self.outputs.is_empty() && self.output_regions.is_empty() && self.syscalls.is_none() {
+            return Err(NeoDemoError::parse("config error: at least one output claim or output region is required"));
+        }
+        for init in &self.ram_init {
+            if init.addr & 3 != 0 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: ram_init address {:#x} is not 4-byte aligned",
+                    init.addr
+                )));
+            }
+            guest_addr("config error: ram_init word", init.addr, 1)?;
+        }
+        for input in &self.inputs {
+            if input.addr & 3 != 0 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: input address {:#x} is not 4-byte aligned",
+                    input.addr
+                )));
+            }
+            guest_addr("config error: input", input.addr, input.words.len())?;
+        }
+        for out in &self.outputs {
+            if out.addr & 3 != 0 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: output address {:#x} is not 4-byte aligned",
+                    out.addr
+                )));
+            }
+            guest_addr("config error: output", out.addr, 1)?;
+        }
+        for region in &self.output_regions {
+            if region.addr & 3 != 0 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: output region address {:#x} is not 4-byte aligned",
+                    region.addr
+                )));
+            }
+            if region.words == 0 {
+                return Err(NeoDemoError::parse(format!("config error: output region at {:#x} is empty", region.addr)));
+            }
+            guest_addr("config error: output region", region.addr, region.words)?;
+        }
+        if let Some(size) = self.ram_bytes {
+            if size == 0 || size & 3 != 0 || size > 1 << 32 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: ram_bytes {size:#x} must be a non-zero multiple of 4 of at most 4 GiB"
+                )));
+            }
+            if self.syscalls.is_some() && size > SAVE_AREA as u64 {
+                return Err(NeoDemoError::parse(format!(
+                    "config error: ram_bytes {size:#x} overlaps the syscall save area at {SAVE_AREA:#x}"
+                )));
+            }
+        }
+        if self.ram_regions().is_some() {
+            for init in &self.ram_init {
+                self.check_in_ram("config error: ram_init word", init.addr, 1)
+                    .map_err(NeoDemoError::parse)?;
+            }
+            for input in &self.inputs {
+                self.check_in_ram("config error: input", input.addr, input.words.len())
+                    .map_err(NeoDemoError::parse)?;
+            }
+            for out in &self.outputs {
+                self.check_in_ram("config error: output", out.addr, 1)
+                    .map_err(NeoDemoError::parse)?;
+            }
+            for region in &self.output_regions {
+                self.check_in_ram("config error: output region", region.addr, region.words)
+                    .map_err(NeoDemoError::parse)?;
+            }
+            if let Some(io) = self.layout().io() {
+                self.check_in_ram("config error: syscall I/O block", io.base, io.words())
+                    .map_err(NeoDemoError::parse)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `validate`, plus what a prebuilt (ELF) guest can't support.
+    pub(crate) fn validate_elf(&self) -> Result<(), NeoDemoError> {
+        if self.syscalls.is_some() {
+            return Err(NeoDemoError::parse(
+                "config error: syscalls are only supported for assembled guests (the assembler links the syscall shim)",
+            ));
+        }
+        self.validate()
+    }
+
+    pub(crate) fn layout(&self) -> Rv32Layout {
+        Rv32Layout {
+            program_base: self.program_base,
+            memory_map: self.memory_map.clone(),
+            syscalls: self.syscalls.clone(),
+        }
+    }
+
+    /// Address ranges loads/stores may touch: the memory map's RAM regions, else
+    /// `[0, ram_bytes)`, else unbounded (`None`).
+    pub(crate) fn ram_regions(&self) -> Option<Vec<Range<u64>>> {
+        self.layout()
+            .ram_regions()
+            .or_else(|| self.ram_bytes.map(|size| std::iter::once(0..size).collect()))
+    }
+
+    /// Error unless `words` RAM words from `addr` fit in one RAM region.
+    fn check_in_ram(&self, what: &str, addr: u64, words: usize) -> Result<(), String> {
+        match self.ram_regions() {
+            Some(regions) => check_in_regions(&regions, what, addr, words),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn exec_max_steps(&self) -> usize {
+        if self.max_steps > 0 {
+            self.max_steps
+        } else {
+            DEFAULT_EXEC_MAX_STEPS
+        }
+    }
+
+    /// Initial RAM words: the program's `.data` segment, overridden by the config's `ram_init`,
+    /// then its `inputs`, then the syscall I/O block.
+    pub(crate) fn ram_init_pairs(&self, program: &Rv32Program) -> Result<Vec<(u32, u32)>, NeoDemoError> {
+        let mut init = self.ram_init.clone();
+        init.extend(self.input_words(|_| true));
+        init.extend(self.layout().io_init());
+        merge_ram_init(program, &init)
+    }
+
+    /// Initial RAM words the public statement doesn't list, other than the syscall I/O block
+    /// (which the layout determines): the config's `ram_init` and non-public `inputs`.
+    pub(crate) fn unlisted_ram_init(&self) -> Vec<Rv32RamInit> {
+        let mut init = self.ram_init.clone();
+        init.extend(self.input_words(|input| !input.public));
+        init
+    }
+
+    /// Words of the `inputs` entries selected by `keep`, one per RAM word.
+    fn input_words(&self, keep: impl Fn(&Rv32Input) -> bool) -> Vec<Rv32RamInit> {
+        self.inputs
+            .iter()
+            .filter(|input| keep(input))
+            .flat_map(|input| {
+                input.words.iter().enumerate().map(|(i, &value)| Rv32RamInit {
+                    addr: input.addr + 4 * i as u64,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    /// Interpreter for `program` under this config, with loads/stores bounded by `ram_regions`.
+    pub(crate) fn machine(&self, program: &Rv32Program) -> Result<Rv32Machine, NeoDemoError> {
+        let layout = self.layout();
+        Ok(
+            Rv32Machine::new(layout.program_base() as u32, &program.rom, &self.ram_init_pairs(program)?)
+                .map_err(|e| NeoDemoError::parse(format!("execute error: {e}")))?
+                .with_ram_regions(self.ram_regions())
+                .with_syscalls(layout.syscall_shim(program)),
+        )
+    }
+
+    /// Run `program` natively before proving, so a guest that faults or leaves RAM fails with its
+    /// PC and address instead of an unprovable trace, then expand `output_regions` into `outputs`
+    /// with the values the run left there and add the claims binding its syscall exit code and
+    /// journal.
+    pub(crate) fn resolve(&self, program: &Rv32Program) -> Result<Rv32Resolved, NeoDemoError> {
+        self.layout().check_program(program)?;
+        for &(addr, _) in &program.ram_init {
+            self.check_in_ram("ram error: .data word", addr as u64, 1)
+                .map_err(NeoDemoError::shape)?;
+        }
+        let mut machine = self.machine(program)?;
+        let steps = machine.run(self.exec_max_steps());
+        match &machine.halted {
+            Some(Rv32HaltReason::Ecall) => {}
+            other => {
+                let reason = other.clone().unwrap_or(Rv32HaltReason::MaxSteps);
+                let err = NeoDemoError::prove(format!(
+                    "native run error: guest did not halt via ecall ({reason}) after {} steps at pc {:#x}",
+                    machine.steps, machine.pc
+                ))
+                .at_step(machine.steps);
+                return Err(match program.line_at(self.layout().program_base() as u32, machine.pc) {
+                    Some(line) => err.at_line(line),
+                    None => err,
+                });
+            }
+        }
+
+        let mut config = self.clone();
+        config.outputs.extend(self.output_regions.iter().flat_map(|r| {
+            (0..r.words as u64).map(|i| {
+                let addr = r.addr + 4 * i;
+                Rv32OutputClaim {
+                    addr,
+                    expected: machine.read_mem(addr as u32, 4),
+                }
+            })
+        }));
+        config.output_regions.clear();
+        let io = machine.io_report();
+        if let (Some(block), Some(report)) = (self.layout().io(), &io) {
+            config.outputs.extend(block.claims(report.exit_code, &report.journal));
+        }
+        check_unlisted(
+            program,
+            &self.layout(),
+            &self.input_words(|input| input.public),
+            &config.outputs,
+            &self.unlisted_ram_init(),
+        )
+        .map_err(|e| NeoDemoError::shape(format!("statement error: {e}")))?;
+        Ok(Rv32Resolved { config, steps, io })
+    }
+}
+
+/// `program`'s `.data` words overridden by `ram_init`, sorted by address.
+pub(crate) fn merge_ram_init(
+    program: &Rv32Program,
+    ram_init: &[Rv32RamInit],
+) -> Result<Vec<(u32, u32)>, NeoDemoError> {
+    let mut words = BTreeMap::new();
+    words.extend(program.ram_init.iter().copied());
+    for init in ram_init {
+        words.insert(guest_addr("ram_init error: word", init.addr, 1)?, init.value);
+    }
+    Ok(words.into_iter().collect())
+}
+
+/// Error unless no word in `unlisted` (initial RAM the statement doesn't list) sets a public
+/// input, a `.data` word of `program` or an output address: otherwise the statement would
+/// describe a run that started from, or was claimed over, memory it doesn't show. Neither
+/// `unlisted` nor the public `inputs` may touch the syscall I/O block, whose initial words
+/// `layout` alone determines.
+pub(crate) fn check_unlisted(
+    program: &Rv32Program,
+    layout: &Rv32Layout,
+    inputs: &[Rv32RamInit],
+    outputs: &[Rv32OutputClaim],
+    unlisted: &[Rv32RamInit],
+) -> Result<(), String> {
+    if let Some(io) = layout.io() {
+        let block = io.range();
+        if let Some(init) = unlisted.iter().chain(inputs).find(|init| block.contains(&init.addr)) {
+            return Err(format!(
+                "RAM word {:#x} is inside the syscall I/O block [{:#x}, {:#x}), which only the layout initializes",
+                init.addr, block.start, block.end
+            ));
+        }
+    }
+    let inputs: BTreeSet<u64> = inputs.iter().map(|init| init.addr).collect();
+    let data: BTreeSet<u64> = program.ram_init.iter().map(|&(addr, _)| addr as u64).collect();
+    let outputs: BTreeSet<u64> = outputs.iter().map(|out| out.addr).collect();
+    for init in unlisted {
+        let bound = if inputs.contains(&init.addr) {
+            "a public input"
+        } else if data.contains(&init.addr) {
+            "a .data word of the program"
+        } else if outputs.contains(&init.addr) {
+            "an output address"
+        } else {
+            continue;
+        };
+        return Err(format!(
+            "RAM word {:#x} is initialized outside the statement but is {bound}",
+            init.addr
+        ));
+    }
+    Ok(())
+}
+
+/// `addr` as a guest address, or a `Shape` error naming `what` unless all `words` words from it
+/// fit in the 32-bit address space (a plain `as u32` would alias `0x1_0000_0100` to `0x100`).
+pub(crate) fn guest_addr(what: &str, addr: u64, words: usize) -> Result<u32, NeoDemoError> {
+    match (u32::try_from(addr), addr.checked_add(4 * words as u64)) {
+        (Ok(addr), Some(end)) if end <= 1 << 32 => Ok(addr),
+        _ => Err(NeoDemoError::shape(format!(
+            "{what} at {addr:#x} ({words} word(s)) lies outside the 32-bit address space"
+        ))),
+    }
+}
+
+/// A config whose output regions were resolved against a checked native run.
+pub(crate) struct Rv32Resolved {
+    pub(crate) config: Rv32ProveConfig,
+    /// The native run, row-aligned with the prover's execution table.
+    pub(crate) steps: Vec<Rv32StepRecord>,
+    pub(crate) io: Option<Rv32IoReport>,
+}
+
+impl Rv32Resolved {
+    /// The public statement a proof of `program` under the resolved config attests to.
+    pub(crate) fn statement(&self, program: &Rv32Program) -> Rv32PublicStatement {
+        Rv32PublicStatement {
+            program_base: self.config.layout().program_base(),
+            program_sha256: program_sha256(program),
+            inputs: self.config.input_words(|input| input.public),
+            outputs: self.config.outputs.clone(),
+            exit_code: self.io.as_ref().map(|io| io.exit_code),
+            journal: self.io.as_ref().map(|io| io.journal.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+/// RAM a run used next to the configured `ram_bytes`, and what the proof made under that
+/// setting measured.
+///
+/// `ram_bytes` bounds the native run before proving; it is not enforced by the proof. The CCS
+/// counts and timings are those of this proof, so comparing them across runs with different
+/// `ram_bytes` shows what (if anything) the setting costs.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32RamUsage {
+    /// Total size of the RAM regions (`ram_bytes`, or the memory map's `ram` + `stack` + `io`).
+    #[tsify(optional)]
+    pub(crate) ram_bytes: Option<u64>,
+    pub(crate) ccs_constraints: usize,
+    pub(crate) ccs_variables: usize,
+    pub(crate) prove_ms: f64,
+    pub(crate) verify_ms: f64,
+    /// Words seeded before execution (`.data`, `ram_init`, `inputs`).
+    pub(crate) init_words: usize,
+    /// Distinct words loaded or stored by the guest.
+    pub(crate) accessed_words: usize,
+    /// Distinct words initialized or accessed.
+    pub(crate) used_words: usize,
+    /// Highest byte address initialized or accessed.
+    #[tsify(optional)]
+    pub(crate) highest_addr: Option<u32>,
+    /// `used_words * 4 / ram_bytes`.
+    #[tsify(optional)]
+    pub(crate) utilization: Option<f64>,
+}
+
+impl Rv32RamUsage {
+    fn new(
+        ram_bytes: Option<u64>,
+        init: &[(u32, u32)],
+        steps: &[Rv32StepRecord],
+        proof: &Rv32ProofSummary,
+        verify_ms: f64,
+    ) -> Self {
+        let accesses: Vec<(u32, u32)> = steps
+            .iter()
+            .filter_map(|rec| rec.mem.as_ref())
+            .map(|m| (m.addr, m.addr.wrapping_add(m.width as u32 - 1)))
+            .collect();
+        let accessed: BTreeSet<u32> = accesses
+            .iter()
+            .flat_map(|&(first, last)| [first & !3, last & !3])
+            .collect();
+        let mut used = accessed.clone();
+        used.extend(init.iter().map(|&(addr, _)| addr));
+        let highest_addr = init
+            .iter()
+            .map(|&(addr, _)| addr.wrapping_add(3))
+            .chain(accesses.iter().map(|&(_, last)| last))
+            .max();
+        Rv32RamUsage {
+            ram_bytes,
+            ccs_constraints: proof.ccs_constraints,
+            ccs_variables: proof.ccs_variables,
+            prove_ms: proof.prove_ms,
+            verify_ms,
+            init_words: init.len(),
+            accessed_words: accessed.len(),
+            used_words: used.len(),
+            highest_addr,
+            utilization: ram_bytes.map(|size| (used.len() as f64 * 4.0) / size as f64),
+        }
+    }
+}
+
+#[derive(serde::Serialize, Tsify)]
+pub(crate) struct Rv32SpartanResult {
+    pub(crate) setup_ms: f64,
+    pub(crate) setup_cached: bool,
+    pub(crate) prove_ms: f64,
+    pub(crate) verify_ms: f64,
+    pub(crate) verify_ok: bool,
+    pub(crate) snark_bytes: usize,
+    pub(crate) snark: Vec<u8>,
+}
+
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32RunResult {
+    pub(crate) outputs: Vec<Rv32OutputClaim>,
+    pub(crate) statement: Rv32PublicStatement,
+    pub(crate) ram: Rv32RamUsage,
+    /// Exit code, journal and debug prints of a guest built with `syscalls`.
+    #[tsify(optional)]
+    pub(crate) io: Option<Rv32IoReport>,
+    pub(crate) verify_ok: bool,
+    pub(crate) prove_ms: f64,
+    pub(crate) verify_ms: f64,
+    #[tsify(optional)]
+    pub(crate) trace_len: Option<usize>,
+    pub(crate) folds: usize,
+    pub(crate) ccs_constraints: usize,
+    pub(crate) ccs_variables: usize,
+    #[tsify(optional)]
+    pub(crate) shout_lookups: Option<usize>,
+    #[tsify(optional)]
+    pub(crate) spartan: Option<Rv32SpartanResult>,
+}
+
+/// Initial RAM and output claims a trace wiring is built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rv32WiringInputs {
+    pub(crate) ram_init: Vec<(u32, u32)>,
+    pub(crate) outputs: Vec<Rv32OutputClaim>,
+}
+
+impl Rv32WiringInputs {
+    pub(crate) fn of(program: &Rv32Program, config: &Rv32ProveConfig) -> Result<Self, NeoDemoError> {
+        Ok(Rv32WiringInputs {
+            ram_init: config.ram_init_pairs(program)?,
+            outputs: config.outputs.clone(),
+        })
+    }
+}
+
+/// Build the trace-wiring prover for `program` (ROM image + `.data`) under `config`.
+pub(crate) fn rv32_wiring(program: &Rv32Program, config: &Rv32ProveConfig) -> Result<Rv32TraceWiring, NeoDemoError> {
+    Ok(wiring(program, config, &Rv32WiringInputs::of(program, config)?))
+}
+
+/// Trace wiring for `program` started from `inputs.ram_init` and claiming `inputs.outputs`.
+fn wiring(program: &Rv32Program, config: &Rv32ProveConfig, inputs: &Rv32WiringInputs) -> Rv32TraceWiring {
+    let mut b = Rv32TraceWiring::from_rom(config.layout().program_base(), &program.rom)
+        .xlen(config.xlen)
+        .chunk_rows(config.chunk_rows)
+        // Picks the Shout lookup tables from the opcodes actually present in the ROM, so RV32M
+        // programs pull in the MUL/DIV/REM tables and pure-RV32I programs don't pay for them.
+        .shout_auto_minimal();
+    for &(addr, value) in &inputs.ram_init {
+        b = b.ram_init_u32(addr as u64, value);
+    }
+    for out in &inputs.outputs {
+        b = b.output(out.addr, F::from_u64(out.expected as u64));
+    }
+    if config.max_steps > 0 {
+        b = b.max_steps(config.max_steps);
+    }
+    b
+}
+
+/// `Shape` error unless `statement` names `program` (by digest) loaded where `config` loads it.
+pub(crate) fn check_rv32_program(
+    program: &Rv32Program,
+    config: &Rv32ProveConfig,
+    statement: &Rv32PublicStatement,
+) -> Result<(), NeoDemoError> {
+    if statement.program_sha256 != program_sha256(program) {
+        return Err(NeoDemoError::shape(
+            "verify error: statement is for a different program (program digest mismatch)",
+        ));
+    }
+    if statement.program_base != config.layout().program_base() {
+        return Err(NeoDemoError::shape(format!(
+            "verify error: statement loads the program at {:#x}, the session at {:#x}",
+            statement.program_base,
+            config.layout().program_base()
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a run proven from `proven` attests to `statement`: the initial RAM is rebuilt from
+/// `program`'s `.data`, the statement's public inputs, the words `config` sets outside the
+/// statement and the syscall I/O header derived from the layout, the output claims from the
+/// statement's outputs, and both must be exactly the ones the run was proven from. The caller
+/// then verifies the run itself.
+pub(crate) fn check_rv32_statement(
+    program: &Rv32Program,
+    config: &Rv32ProveConfig,
+    statement: &Rv32PublicStatement,
+    proven: &Rv32WiringInputs,
+) -> Result<(), NeoDemoError> {
+    check_rv32_program(program, config, statement)?;
+    // The decoded exit code and journal must be the ones the output claims bind.
+    if let Some(io) = config.layout().io() {
+        let exit_code = statement.exit_code.ok_or_else(|| {
+            NeoDemoError::verify("verify error: statement has no exit code for a guest built with syscalls")
+        })?;
+        if let Some(claim) = io
+            .claims(exit_code, &statement.journal)
+            .into_iter()
+            .find(|claim| !statement.outputs.contains(claim))
+        {
+            return Err(NeoDemoError::verify(format!(
+                "verify error: statement exit code/journal are not bound by its outputs (missing RAM[{:#x}] == {:#x})",
+                claim.addr, claim.expected
+            )));
+        }
+    }
+
+    let unlisted = config.unlisted_ram_init();
+    check_unlisted(program, &config.layout(), &statement.inputs, &statement.outputs, &unlisted)
+        .map_err(|e| NeoDemoError::verify(format!("verify error: {e}")))?;
+    let mut init = unlisted;
+    init.extend(statement.inputs.iter().cloned());
+    init.extend(config.layout().io_init());
+    let expected = Rv32WiringInputs {
+        ram_init: merge_ram_init(program, &init)?,
+        outputs: statement.outputs.clone(),
+    };
+    if expected.ram_init != proven.ram_init {
+        return Err(NeoDemoError::verify(
+            "verify error: proof was made from a different initial RAM than the statement describes",
+        ));
+    }
+    if expected.outputs != proven.outputs {
+        return Err(NeoDemoError::verify(
+            "verify error: proof claims different outputs than the statement lists",
+        ));
+    }
+    Ok(())
+}
+
+/// Spartan proof for an RV32 run plus the verifier key it checks against.
+pub(crate) struct Rv32SpartanProof {
+    pub(crate) proof: neo_spartan_bridge::api::SpartanProof,
+    pub(crate) vk: neo_spartan_bridge::SpartanVerifierKey,
+    pub(crate) setup_ms: f64,
+    pub(crate) setup_cached: bool,
+    pub(crate) prove_ms: f64,
+}
+
+/// Compress an RV32 run with Spartan, reusing cached keys when the shape matches.
+pub(crate) fn rv32_spartan_prove(run: &Rv32TraceWiringRun) -> Result<Rv32SpartanProof, NeoDemoError> {
+    let acc_init = &[];
+    let witness = fold_run_witness_placeholder(run.proof());
+    let digest = circuit_digest(run.params(), run.ccs())?;
+    let keys_id = spartan_keys_id(&digest, run.proof().steps.len());
+
+    RV32_SPARTAN_KEYS.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let (keys, setup_cached) = SpartanKeys::get_or_setup(&mut slot, keys_id, || {
+            neo_spartan_bridge::setup_fold_run(run.params(), run.ccs(), acc_init, run.proof(), witness.clone())
+                .map(|kp| (kp.pk, kp.vk))
+                .map_err(|e| NeoDemoError::spartan_setup(format!("spartan setup error: {e}")))
+        })?;
+        let setup_ms = if setup_cached { 0.0 } else { keys.setup_ms };
+
+        let prove_start = Date::now();
+        let proof = neo_spartan_bridge::prove_fold_run(&keys.pk, run.params(), run.ccs(), acc_init, run.proof(), witness)
+            .map_err(|e| NeoDemoError::spartan_prove(format!("spartan prove error: {e}")))?;
+        let prove_ms = Date::now() - prove_start;
+
+        Ok(Rv32SpartanProof {
+            proof,
+            vk: keys.vk.clone(),
+            setup_ms,
+            setup_cached,
+            prove_ms,
+        })
+    })
+}
+
+/// Compress a verified RV32 run with Spartan and verify the result.
+pub(crate) fn rv32_spartan(run: &Rv32TraceWiringRun) -> Result<Rv32SpartanResult, NeoDemoError> {
+    let compressed = rv32_spartan_prove(run)?;
+
+    let verify_start = Date::now();
+    let verify_ok = neo_spartan_bridge::verify_fold_run(&compressed.vk, run.params(), run.ccs(), &compressed.proof)
+        .map_err(|e| NeoDemoError::spartan_verify(format!("spartan verify error: {e}")))?;
+    let verify_ms = Date::now() - verify_start;
+
+    let snark = compressed.proof.snark_data;
+    let snark_bytes = snark.len();
+
+    Ok(Rv32SpartanResult {
+        setup_ms: compressed.setup_ms,
+        setup_cached: compressed.setup_cached,
+        prove_ms: compressed.prove_ms,
+        verify_ms,
+        verify_ok,
+        snark_bytes,
+        snark,
+    })
+}
+
+/// Shape/size summary of a proved RV32 run.
+#[derive(serde::Serialize, Tsify)]
+pub struct Rv32ProofSummary {
+    pub(crate) prove_ms: f64,
+    pub(crate) trace_len: usize,
+    pub(crate) folds: usize,
+    pub(crate) ccs_constraints: usize,
+    pub(crate) ccs_variables: usize,
+    pub(crate) shout_lookups: usize,
+}
+
+impl Rv32ProofSummary {
+    pub(crate) fn of(run: &Rv32TraceWiringRun) -> Self {
+        Rv32ProofSummary {
+            prove_ms: run.prove_duration().as_secs_f64() * 1000.0,
+            trace_len: run.trace_len(),
+            folds: run.fold_count(),
+            ccs_constraints: run.ccs_num_constraints(),
+            ccs_variables: run.ccs_num_variables(),
+            shout_lookups: run.exec_table().rows.iter().map(|r| r.shout_events.len()).sum(),
+        }
+    }
+}
+
+/// Prove + verify `program` under `config` (and optionally Spartan-compress it).
+pub(crate) fn prove_verify_rv32(
+    program: &Rv32Program,
+    config: &Rv32ProveConfig,
+) -> Result<Rv32RunResult, NeoDemoError> {
+    config.validate()?;
+    if program.rom.is_empty() {
+        return Err(NeoDemoError::assemble("assembled program is empty"));
+    }
+    let resolved = config.resolve(program)?;
+    let config = &resolved.config;
+
+    let mut run = rv32_wiring(program, config)?
+        .prove()
+        .map_err(|e| NeoDemoError::prove(format!("prove error: {e}")))?;
+
+    run.verify()
+        .map_err(|e| NeoDemoError::verify(format!("verify error: {e}")))?;
+    let verify_ok = true;
+    let verify_ms = run
+        .verify_duration()
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0);
+
+    let spartan = if config.do_spartan {
+        Some(rv32_spartan(&run)?)
+    } else {
+        None
+    };
+
+    let summary = Rv32ProofSummary::of(&run);
+    let ram_bytes = config.ram_regions().map(|regions| regions.iter().map(|r| r.end - r.start).sum());
+    let ram = Rv32RamUsage::new(ram_bytes, &config.ram_init_pairs(program)?, &resolved.steps, &summary, verify_ms);
+    Ok(Rv32RunResult {
+        outputs: config.outputs.clone(),
+        statement: resolved.statement(program),
+        ram,
+        io: resolved.io,
+        verify_ok,
+        prove_ms: summary.prove_ms,
+        verify_ms,
+        trace_len: Some(summary.trace_len),
+        folds: summary.folds,
+        ccs_constraints: summary.ccs_constraints,
+        ccs_variables: summary.ccs_variables,
+        shout_lookups: Some(summary.shout_lookups),
+        spartan,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NeoDemoErrorKind;
+    use crate::riscv_asm::assemble_rv32;
+    use crate::rv32_syscall::Rv32SyscallConfig;
+
+    const SRC: &str = "
+.data
+scale: .word 3
+.text
+    lw   a0, 0x200(x0)
+    lw   a1, 0x204(x0)
+    la   t0, scale
+    lw   t0, 0(t0)
+    mul  a0, a0, t0
+    add  a0, a0, a1
+    sw   a0, 0x100(x0)
+    ecall
+";
+
+    fn program() -> Rv32Program {
+        let out = assemble_rv32(SRC, &HashMap::new(), &Rv32AsmLayout::default());
+        out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics))
+    }
+
+    /// `RAM[0x100] = 3 * RAM[0x200] + RAM[0x204]`, with `0x200` public and `0x204` private.
+    fn guest_config(private: u32, output: u32) -> Rv32ProveConfig {
+        Rv32ProveConfig {
+            program_base: None,
+            memory_map: None,
+            syscalls: None,
+            xlen: 32,
+            ram_init: Vec::new(),
+            inputs: vec![
+                Rv32Input {
+                    addr: 0x200,
+                    words: vec![5],
+                    public: true,
+                },
+                Rv32Input {
+                    addr: 0x204,
+                    words: vec![private],
+                    public: false,
+                },
+            ],
+            outputs: vec![Rv32OutputClaim {
+                addr: 0x100,
+                expected: output,
+            }],
+            output_regions: Vec::new(),
+            chunk_rows: 4,
+            ram_bytes: None,
+            max_steps: 0,
+            do_spartan: false,
+            includes: HashMap::new(),
+        }
+    }
+
+    fn statement(program: &Rv32Program, config: &Rv32ProveConfig) -> Rv32PublicStatement {
+        Rv32PublicStatement {
+            program_base: 0,
+            program_sha256: program_sha256(program),
+            inputs: config.input_words(|input| input.public),
+            outputs: config.outputs.clone(),
+            exit_code: None,
+            journal: Vec::new(),
+        }
+    }
+
+    fn verify_err(result: Result<(), NeoDemoError>) -> NeoDemoError {
+        let err = result.expect_err("statement should not verify");
+        assert_eq!(err.kind, NeoDemoErrorKind::Verify, "{}", err.message);
+        err
+    }
+
+    #[test]
+    fn statement_binds_the_unlisted_initial_ram() {
+        let program = program();
+        let config = guest_config(2, 17);
+        let statement = statement(&program, &config);
+        let proven = Rv32WiringInputs::of(&program, &config).unwrap();
+        check_rv32_statement(&program, &config, &statement, &proven).unwrap();
+
+        // A run proven from another private input (or any other unlisted word) is not a proof of
+        // this statement, even with the same public inputs and outputs.
+        let tampered = Rv32WiringInputs::of(&program, &guest_config(4, 17)).unwrap();
+        verify_err(check_rv32_statement(&program, &config, &statement, &tampered));
+        let mut extra = proven.clone();
+        extra.ram_init.push((0x300, 1));
+        verify_err(check_rv32_statement(&program, &config, &statement, &extra));
+
+        let mut other_output = statement.clone();
+        other_output.outputs[0].expected = 18;
+        verify_err(check_rv32_statement(&program, &config, &other_output, &proven));
+    }
+
+    #[test]
+    fn proofs_are_checked_against_the_session_program_and_base() {
+        let program = program();
+        let config = guest_config(2, 17);
+        let statement = statement(&program, &config);
+        check_rv32_program(&program, &config, &statement).unwrap();
+
+        let out = assemble_rv32("ecall\n", &HashMap::new(), &Rv32AsmLayout::default());
+        let other = out.program.unwrap();
+        let err = check_rv32_program(&other, &config, &statement).expect_err("other program");
+        assert_eq!(err.kind, NeoDemoErrorKind::Shape);
+        assert!(err.message.contains("digest mismatch"), "{}", err.message);
+
+        let mut moved = config.clone();
+        moved.program_base = Some(0x8000_0000);
+        let err = check_rv32_program(&program, &moved, &statement).expect_err("other program base");
+        assert_eq!(err.kind, NeoDemoErrorKind::Shape);
+        assert!(err.message.contains("0x80000000"), "{}", err.message);
+    }
+
+    #[test]
+    fn unlisted_words_must_not_cover_what_the_statement_binds() {
+        let program = program();
+        let scale = program.ram_init[0].0 as u64;
+        let cases = [
+            (Rv32RamInit { addr: scale, value: 4 }, "a .data word"),
+            (Rv32RamInit { addr: 0x100, value: 17 }, "an output address"),
+            (Rv32RamInit { addr: 0x200, value: 6 }, "a public input"),
+        ];
+        for (init, what) in cases {
+            let mut config = guest_config(2, 17);
+            config.ram_init.push(init.clone());
+            let statement = statement(&program, &config);
+            let proven = Rv32WiringInputs::of(&program, &config).unwrap();
+            let err = verify_err(check_rv32_statement(&program, &config, &statement, &proven));
+            assert!(err.message.contains(what), "{:#x}: {}", init.addr, err.message);
+        }
+    }
+
+    const IO_GUEST: &str = "
+    li   a7, SYS_READ
+    ecall
+    add  a0, a0, a0
+    li   a7, SYS_COMMIT
+    ecall
+    li   a0, 0
+    li   a7, SYS_HALT
+    ecall
+";
+
+    fn io_config() -> Rv32ProveConfig {
+        Rv32ProveConfig {
+            syscalls: Some(Rv32SyscallConfig {
+                input: vec![21],
+                journal_words: 4,
+            }),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            ..guest_config(0, 0)
+        }
+    }
+
+    fn io_program(config: &Rv32ProveConfig) -> Rv32Program {
+        let out = assemble_rv32(IO_GUEST, &HashMap::new(), &config.layout().asm_layout());
+        out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics))
+    }
+
+    #[test]
+    fn io_header_comes_from_the_layout() {
+        let config = io_config();
+        let program = io_program(&config);
+        let resolved = config.resolve(&program).unwrap();
+        let statement = resolved.statement(&program);
+        assert_eq!((statement.exit_code, statement.journal.as_slice()), (Some(0), &[42][..]));
+        let proven = Rv32WiringInputs::of(&program, &resolved.config).unwrap();
+        check_rv32_statement(&program, &config, &statement, &proven).unwrap();
+
+        // A run that started with the journal already holding the claimed word (and its cursor
+        // past it) proves nothing about what the guest committed.
+        let io = DEFAULT_IO_BASE;
+        let mut seeded = proven.clone();
+        for (addr, value) in [(io + 8, io + 36), (io + 32, 42)] {
+            match seeded.ram_init.iter_mut().find(|(a, _)| *a == addr) {
+                Some(word) => word.1 = value,
+                None => seeded.ram_init.push((addr, value)),
+            }
+        }
+        seeded.ram_init.sort();
+        verify_err(check_rv32_statement(&program, &config, &statement, &seeded));
+
+        // Seeding the block from the config is refused before proving.
+        for addr in [io + 8, io + 32] {
+            let mut config = io_config();
+            config.ram_init.push(Rv32RamInit {
+                addr: addr as u64,
+                value: 0,
+            });
+            let err = config.resolve(&program).err().expect("ram_init inside the I/O block");
+            assert_eq!(err.kind, NeoDemoErrorKind::Shape);
+            assert!(err.message.contains("syscall I/O block"), "{}", err.message);
+        }
+    }
+
+    #[test]
+    fn memory_maps_keep_clear_of_reserved_ranges() {
+        let layout = |ram: (u64, u64), stack: Option<(u64, u64)>, io: Option<(u64, u64)>| Rv32Layout {
+            program_base: None,
+            memory_map: Some(Rv32MemoryMap {
+                rom: Rv32Region { base: 0, size: 0x1000 },
+                ram: Rv32Region {
+                    base: ram.0,
+                    size: ram.1,
+                },
+                stack: stack.map(|(base, size)| Rv32Region { base, size }),
+                io: io.map(|(base, size)| Rv32Region { base, size }),
+            }),
+            syscalls: Some(Rv32SyscallConfig {
+                input: vec![1, 2],
+                journal_words: 4,
+            }),
+        };
+        let rejects = |layout: Rv32Layout, what: &str| {
+            let err = layout.validate().expect_err(what);
+            assert!(err.message.contains(what), "{}", err.message);
+        };
+
+        rejects(layout((0x8000, 0x10000), None, None), "overlaps the syscall I/O block");
+        rejects(layout((0x2000, 0x1000), Some((0xffff_0000, 0x1_0000)), None), "save area");
+        rejects(layout((0x2000, 0x1000), None, Some((0xffff_ff00, 0x100))), "save area");
+        rejects(layout((0x2000, 0x1000), None, Some((0x3000, 16))), "does not fit in memory_map.io");
+
+        // Without an `io` region the block at `DEFAULT_IO_BASE` is reachable on its own.
+        let ok = layout((0x2000, 0x1000), None, None);
+        ok.validate().unwrap();
+        let block = ok.io().unwrap().range();
+        assert_eq!(block.start, DEFAULT_IO_BASE as u64);
+        assert!(ok.ram_regions().unwrap().contains(&block));
+        layout((0x2000, 0x1000), None, Some((0x3000, 0x1000))).validate().unwrap();
+    }
+
+    #[test]
+    fn memory_mapped_guests_run_at_their_base() {
+        let region = |base, size| Rv32Region { base, size };
+        let mut config = guest_config(0, 0);
+        config.inputs.clear();
+        config.outputs.clear();
+        config.output_regions.push(Rv32OutputRegion {
+            addr: 0x8001_0100,
+            words: 1,
+        });
+        config.memory_map = Some(Rv32MemoryMap {
+            rom: region(0x8000_0000, 0x1000),
+            ram: region(0x8001_0000, 0x1000),
+            stack: Some(region(0x8002_0000, 0x1000)),
+            io: None,
+        });
+        config.validate().unwrap();
+        let assemble = |src: &str| {
+            let out = assemble_rv32(src, &HashMap::new(), &config.layout().asm_layout());
+            out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics))
+        };
+
+        let program = assemble(
+            "
+.data
+value: .word 7
+.text
+    li   sp, __stack_top
+    la   t0, value
+    lw   a0, 0(t0)
+    call double
+    li   t1, __ram_start
+    sw   a0, 0x100(t1)
+    ecall
+double:
+    addi sp, sp, -4
+    sw   a0, 0(sp)
+    lw   a0, 0(sp)
+    add  a0, a0, a0
+    addi sp, sp, 4
+    ret
+",
+        );
+        assert_eq!(program.ram_init, [(0x8001_0000, 7)]);
+        let double = program.symbols.iter().find(|(name, _)| name == "double").unwrap().1;
+        assert_eq!(double, 0x8000_0000 + 4 * program.lines.iter().position(|&l| l == 13).unwrap() as u32);
+        let resolved = config.resolve(&program).unwrap();
+        assert_eq!(resolved.config.outputs[0].expected, 14);
+        assert_eq!(resolved.statement(&program).program_base, 0x8000_0000);
+
+        // Loads/stores outside the mapped regions fault, as does a ROM larger than the map allows.
+        let err = config.resolve(&assemble("    li t1, __ram_end\n    sw zero, 0(t1)\n    ecall\n")).err().unwrap();
+        assert!(err.message.contains("out-of-bounds store"), "{}", err.message);
+        let mut small = config.clone();
+        small.memory_map.as_mut().unwrap().rom.size = 8;
+        let err = small.resolve(&program).err().unwrap();
+        assert_eq!(err.kind, NeoDemoErrorKind::Shape);
+        assert!(err.message.contains("larger than memory_map.rom"), "{}", err.message);
+    }
+
+    #[test]
+    fn ram_bytes_bounds_the_native_run() {
+        let bounded = |src: &str| {
+            let out = assemble_rv32(src, &HashMap::new(), &Rv32AsmLayout::default());
+            let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+            let mut config = guest_config(0, 0);
+            config.inputs.clear();
+            config.outputs.clear();
+            config.output_regions.push(Rv32OutputRegion { addr: 0x100, words: 1 });
+            config.ram_bytes = Some(0x200);
+            (program, config)
+        };
+
+        let (program, config) = bounded("li a0, 1\nsw a0, 0x100(x0)\necall\n");
+        assert_eq!(config.resolve(&program).unwrap().config.outputs[0].expected, 1);
+
+        // A guest store past the bound fails before proving, naming the step, line, PC and address.
+        let (program, config) = bounded("li a0, 1\nsw a0, 0x1fc(x0)\nsw a0, 0x200(x0)\necall\n");
+        let err = config.resolve(&program).err().expect("store past ram_bytes");
+        assert_eq!((err.kind, err.step, err.line), (NeoDemoErrorKind::Prove, Some(2), Some(3)));
+        assert!(
+            err.message.contains("out-of-bounds store of 4 byte(s) at 0x200 by pc 0x8"),
+            "{}",
+            err.message
+        );
+        let (program, config) = bounded("lw a0, 0x400(x0)\necall\n");
+        let err = config.resolve(&program).err().expect("load past ram_bytes");
+        assert!(err.message.contains("out-of-bounds load"), "{}", err.message);
+
+        // So do words the config or the program initializes past it.
+        let (_, mut config) = bounded("ecall\n");
+        config.ram_init.push(Rv32RamInit { addr: 0x200, value: 0 });
+        let err = config.validate().expect_err("ram_init past ram_bytes");
+        assert!(err.message.contains("0x200"), "{}", err.message);
+        config.ram_init.clear();
+        config.output_regions[0].addr = 0x1fc;
+        config.output_regions[0].words = 2;
+        config.validate().expect_err("output region straddling ram_bytes");
+        let (program, config) = bounded(".data\n.word 1\n.text\necall\n");
+        assert!(program.ram_init.iter().all(|&(addr, _)| addr >= 0x200));
+        let err = config.resolve(&program).err().expect(".data past ram_bytes");
+        assert!(err.message.contains(".data word"), "{}", err.message);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use deprecated_neo_memory::riscv::lookups::{
+    decode_instruction, BranchCondition, RiscvInstruction, RiscvMemOp, RiscvOpcode,
+};
+use tsify::Tsify;
+
+const ABI_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7",
+    "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
+const EBREAK: u32 = 0x0010_0073;
+
+/// One disassembled ROM word.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32ListingRow {
+    pub(crate) addr: u32,
+    pub(crate) word: u32,
+    pub(crate) word_hex: String,
+    /// Label defined at `addr`, if known.
+    #[tsify(optional)]
+    pub(crate) label: Option<String>,
+    /// Mini-asm text that re-assembles to `word` (raw hex for words that don't decode; `ebreak`
+    /// is printed but not assemblable).
+    pub(crate) text: String,
+    /// Absolute target of a branch/`jal`.
+    #[tsify(optional)]
+    pub(crate) target: Option<u32>,
+    #[tsify(optional)]
+    pub(crate) target_label: Option<String>,
+    /// 1-based source line that produced this word, when disassembling an assembled program.
+    #[tsify(optional)]
+    pub(crate) line: Option<usize>,
+    #[tsify(optional)]
+    pub(crate) source: Option<String>,
+}
+
+/// Disassembly listing, one row per ROM word.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct Rv32Listing(pub(crate) Vec<Rv32ListingRow>);
+
+fn reg(r: u8) -> &'static str {
+    ABI_NAMES[r as usize & 31]
+}
+
+fn alu_mnemonic(op: RiscvOpcode) -> Option<&'static str> {
+    Some(match op {
+        RiscvOpcode::Add => "add",
+        RiscvOpcode::Sub => "sub",
+        RiscvOpcode::And => "and",
+        RiscvOpcode::Or => "or",
+        RiscvOpcode::Xor => "xor",
+        RiscvOpcode::Sll => "sll",
+        RiscvOpcode::Srl => "srl",
+        RiscvOpcode::Sra => "sra",
+        RiscvOpcode::Slt => "slt",
+        RiscvOpcode::Sltu => "sltu",
+        RiscvOpcode::Mul => "mul",
+        RiscvOpcode::Mulh => "mulh",
+        RiscvOpcode::Mulhu => "mulhu",
+        RiscvOpcode::Mulhsu => "mulhsu",
+        RiscvOpcode::Div => "div",
+        RiscvOpcode::Divu => "divu",
+        RiscvOpcode::Rem => "rem",
+        RiscvOpcode::Remu => "remu",
+        _ => return None,
+    })
+}
+
+fn mem_mnemonic(op: RiscvMemOp) -> Option<&'static str> {
+    Some(match op {
+        RiscvMemOp::Lb => "lb",
+        RiscvMemOp::Lh => "lh",
+        RiscvMemOp::Lw => "lw",
+        RiscvMemOp::Lbu => "lbu",
+        RiscvMemOp::Lhu => "lhu",
+        RiscvMemOp::Sb => "sb",
+        RiscvMemOp::Sh => "sh",
+        RiscvMemOp::Sw => "sw",
+        _ => return None,
+    })
+}
+
+fn branch_mnemonic(cond: BranchCondition) -> &'static str {
+    match cond {
+        BranchCondition::Eq => "beq",
+        BranchCondition::Ne => "bne",
+        BranchCondition::Lt => "blt",
+        BranchCondition::Ge => "bge",
+        BranchCondition::Ltu => "bltu",
+        BranchCondition::Geu => "bgeu",
+    }
+}
+
+/// Format `instr`, decoded from `word`, as canonical (non-pseudo) mini-asm. Branch/`jal` targets
+/// use the label at the target address when one is known and the relative byte offset otherwise,
+/// so the text re-assembles to the same word either way. `lui`/`auipc` print the raw 20-bit field
+/// of `word`. `None` for instructions the assembler can't express.
+fn format_instruction(
+    instr: &RiscvInstruction,
+    word: u32,
+    pc: u32,
+    labels: &HashMap<u32, String>,
+) -> Option<(String, Option<u32>)> {
+    let target_text = |imm: i32| {
+        let target = pc.wrapping_add(imm as u32);
+        let text = labels.get(&target).cloned().unwrap_or_else(|| imm.to_string());
+        (text, target)
+    };
+    Some(match *instr {
+        RiscvInstruction::RAlu { op, rd, rs1, rs2 } => {
+            (format!("{} {}, {}, {}", alu_mnemonic(op)?, reg(rd), reg(rs1), reg(rs2)), None)
+        }
+        RiscvInstruction::IAlu { op, rd, rs1, imm } => {
+            let mnemonic = match op {
+                RiscvOpcode::Add => "addi",
+                RiscvOpcode::And => "andi",
+                RiscvOpcode::Or => "ori",
+                RiscvOpcode::Xor => "xori",
+                RiscvOpcode::Slt => "slti",
+                RiscvOpcode::Sltu => "sltiu",
+                RiscvOpcode::Sll => "slli",
+                RiscvOpcode::Srl => "srli",
+                RiscvOpcode::Sra => "srai",
+                _ => return None,
+            };
+            let imm = if matches!(op, RiscvOpcode::Sll | RiscvOpcode::Srl | RiscvOpcode::Sra) {
+                imm & 31
+            } else {
+                imm
+            };
+            (format!("{mnemonic} {}, {}, {imm}", reg(rd), reg(rs1)), None)
+        }
+        RiscvInstruction::Load { op, rd, rs1, imm } => {
+            (format!("{} {}, {imm}({})", mem_mnemonic(op)?, reg(rd), reg(rs1)), None)
+        }
+        RiscvInstruction::Store { op, rs1, rs2, imm } => {
+            (format!("{} {}, {imm}({})", mem_mnemonic(op)?, reg(rs2), reg(rs1)), None)
+        }
+        RiscvInstruction::Branch { cond, rs1, rs2, imm } => {
+            let (text, target) = target_text(imm);
+            (format!("{} {}, {}, {text}", branch_mnemonic(cond), reg(rs1), reg(rs2)), Some(target))
+        }
+        RiscvInstruction::Jal { rd, imm } => {
+            let (text, target) = target_text(imm);
+            (format!("jal {}, {text}", reg(rd)), Some(target))
+        }
+        RiscvInstruction::Jalr { rd, rs1, imm } => (format!("jalr {}, {imm}({})", reg(rd), reg(rs1)), None),
+        RiscvInstruction::Lui { rd, .. } => (format!("lui {}, {:#x}", reg(rd), word >> 12), None),
+        RiscvInstruction::Auipc { rd, .. } => (format!("auipc {}, {:#x}", reg(rd), word >> 12), None),
+        RiscvInstruction::Nop => ("nop".to_string(), None),
+        RiscvInstruction::Halt => ("ecall".to_string(), None),
+        _ => return None,
+    })
+}
+
+/// Text and branch target for `word` at `pc` (raw hex if it doesn't decode). `ebreak` is told
+/// apart from `ecall`, though the assembler doesn't accept it.
+fn format_word(word: u32, pc: u32, labels: &HashMap<u32, String>) -> (String, Option<u32>) {
+    if word == EBREAK {
+        return ("ebreak".to_string(), None);
+    }
+    decode_instruction(word)
+        .ok()
+        .and_then(|instr| format_instruction(&instr, word, pc, labels))
+        .unwrap_or_else(|| (format!("{word:#010x}"), None))
+}
+
+/// Mini-asm text for a single `word` at `pc`, without labels (raw hex if it doesn't decode).
+pub(crate) fn disassemble_word(word: u32, pc: u32) -> String {
+    format_word(word, pc, &HashMap::new()).0
+}
+
+/// Disassemble a ROM image loaded at `program_base`.
+///
+/// `symbols` names addresses (text labels become row labels and branch targets), `lines` gives
+/// the source line of each word, and `source` is the text those lines index into. All three
+/// may be empty when only raw bytes are available.
+pub(crate) fn disassemble_rv32(
+    rom: &[u8],
+    program_base: u32,
+    symbols: &[(String, u32)],
+    lines: &[usize],
+    source: Option<&str>,
+) -> Result<Vec<Rv32ListingRow>, String> {
+    if rom.len() & 3 != 0 {
+        return Err(format!("disassemble error: {} bytes is not a whole number of words", rom.len()));
+    }
+    let mut labels = HashMap::new();
+    for (name, addr) in symbols {
+        labels.entry(*addr).or_insert_with(|| name.clone());
+    }
+    let source_lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+
+    let rows = rom
+        .chunks_exact(4)
+        .enumerate()
+        .map(|(i, c)| {
+            let addr = program_base.wrapping_add(4 * i as u32);
+            let word = u32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+            let (text, target) = format_word(word, addr, &labels);
+            let line = lines.get(i).copied().filter(|&l| l > 0);
+            Rv32ListingRow {
+                addr,
+                word,
+                word_hex: format!("{word:08x}"),
+                label: labels.get(&addr).cloned(),
+                text,
+                target,
+                target_label: target.and_then(|t| labels.get(&t).cloned()),
+                line,
+                source: line.and_then(|l| source_lines.get(l - 1)).map(|s| s.trim().to_string()),
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::riscv_asm::{assemble_rv32, Rv32AsmLayout};
+
+    const BASE: u32 = 0x8000_0000;
+
+    fn assemble(src: &str) -> crate::riscv_asm::Rv32Program {
+        let layout = Rv32AsmLayout {
+            text_base: BASE,
+            ..Rv32AsmLayout::default()
+        };
+        let out = assemble_rv32(src, &HashMap::new(), &layout);
+        out.program.unwrap_or_else(|| panic!("{src:?}: {:?}", out.diagnostics))
+    }
+
+    #[test]
+    fn u_type_listing_round_trips() {
+        let src = "start:\n    lui a0, 0x12345\n    auipc t1, 0x80000\n    lui a2, 0xfffff\n    lui a3, 0\n    ecall\n";
+        let program = assemble(src);
+        let rows = disassemble_rv32(&program.rom, BASE, &program.symbols, &program.lines, Some(src)).unwrap();
+        assert_eq!(rows[0].word, 0x1234_5537);
+        assert_eq!(rows[0].label.as_deref(), Some("start"));
+        let texts: Vec<&str> = rows.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["lui a0, 0x12345", "auipc t1, 0x80000", "lui a2, 0xfffff", "lui a3, 0x0", "ecall"]);
+        assert_eq!(rows[1].source.as_deref(), Some("auipc t1, 0x80000"));
+
+        let relisted = assemble(&texts.join("\n"));
+        assert_eq!(relisted.rom, program.rom);
+    }
+
+    #[test]
+    fn ebreak_is_not_listed_as_ecall() {
+        assert_eq!(disassemble_word(0x0000_0073, 0), "ecall");
+        assert_eq!(disassemble_word(0x0010_0073, 0), "ebreak");
+    }
+}
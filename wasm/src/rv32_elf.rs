@@ -0,0 +1,400 @@
+use deprecated_neo_memory::riscv::lookups::{decode_instruction, encode_program, RiscvInstruction};
+
+use crate::riscv_asm::Rv32Program;
+use crate::rv32_exec::rv32_supported;
+
+const EM_RISCV: u16 = 0xf3;
+const ET_EXEC: u16 = 2;
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 1;
+const SHT_PROGBITS: u32 = 1;
+const SHF_ALLOC: u32 = 2;
+const SHF_EXECINSTR: u32 = 4;
+/// `addi x0, x0, 0`, filling ROM words between executable sections.
+const NOP: u32 = 0x0000_0013;
+/// Farthest `jal` target: its immediate is a signed 21-bit byte offset.
+const JAL_MAX_OFFSET: u32 = (1 << 20) - 2;
+
+/// Guest image loaded from an RV32 ELF executable.
+pub(crate) struct Rv32ElfImage {
+    /// Address of the first ROM word (one word below the text when a trampoline was needed).
+    pub(crate) program_base: u32,
+    pub(crate) entry: u32,
+    pub(crate) program: Rv32Program,
+}
+
+/// Little-endian ELF32 field reader that turns truncation into an error instead of a panic.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn slice(&self, off: usize, len: usize, what: &str) -> Result<&[u8], String> {
+        off.checked_add(len)
+            .and_then(|end| self.bytes.get(off..end))
+            .ok_or_else(|| format!("elf error: truncated {what} at offset {off:#x}"))
+    }
+
+    /// Offset of entry `i` in a header table at `table` with `size`-byte entries.
+    fn table_entry(&self, table: usize, i: usize, size: usize, what: &str) -> Result<usize, String> {
+        i.checked_mul(size)
+            .and_then(|off| table.checked_add(off))
+            .ok_or_else(|| format!("elf error: {what} {i} of the table at {table:#x} is past the end of memory"))
+    }
+
+    fn u16(&self, off: usize, what: &str) -> Result<u16, String> {
+        let b = self.slice(off, 2, what)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&self, off: usize, what: &str) -> Result<u32, String> {
+        let b = self.slice(off, 4, what)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+struct Segment<'a> {
+    vaddr: u32,
+    data: &'a [u8],
+    exec: bool,
+}
+
+/// An allocated `PROGBITS` section: `[start, end)` plus whether it holds code.
+struct SectionRange {
+    start: u32,
+    end: u32,
+    exec: bool,
+}
+
+/// Allocated `PROGBITS` sections (empty if there is no section header table).
+fn alloc_sections(r: &Reader) -> Result<Vec<SectionRange>, String> {
+    let shoff = r.u32(0x20, "header")? as usize;
+    let shentsize = r.u16(0x2e, "header")? as usize;
+    let shnum = r.u16(0x30, "header")? as usize;
+    if shoff == 0 || shnum == 0 {
+        return Ok(Vec::new());
+    }
+    let mut ranges = Vec::new();
+    for i in 0..shnum {
+        let sh = r.table_entry(shoff, i, shentsize, "section header")?;
+        let sh_type = r.u32(sh + 0x04, "section header")?;
+        let flags = r.u32(sh + 0x08, "section header")?;
+        if sh_type != SHT_PROGBITS || flags & SHF_ALLOC == 0 {
+            continue;
+        }
+        let addr = r.u32(sh + 0x0c, "section header")?;
+        let size = r.u32(sh + 0x14, "section header")?;
+        if size > 0 {
+            ranges.push(SectionRange {
+                start: addr,
+                end: addr.wrapping_add(size),
+                exec: flags & SHF_EXECINSTR != 0,
+            });
+        }
+    }
+    Ok(ranges)
+}
+
+/// Parse an RV32 little-endian ELF executable into ROM + RAM initialization.
+///
+/// Executable `PT_LOAD` segments become the ROM; when section headers are present only the bytes
+/// of executable sections do, so read-only data linked into the same segment is loaded into RAM
+/// instead of being decoded as instructions, and only bytes inside allocated sections become RAM
+/// words (skipping ELF headers that the linker maps into the first segment). ROM words between
+/// executable ranges are `nop`s. `.bss` is left to RAM's zero default. Every ROM word must
+/// decode to an instruction the RV32 trace supports; the first one that doesn't is reported
+/// with its address.
+///
+/// The trace starts executing at the first ROM word, so if the entry point is elsewhere a
+/// `jal x0, entry` trampoline is placed one word below the text and becomes `program_base`; the
+/// entry point must then be within `jal` range of it.
+pub(crate) fn load_rv32_elf(bytes: &[u8]) -> Result<Rv32ElfImage, String> {
+    let r = Reader { bytes };
+    let ident = r.slice(0, 16, "header")?;
+    if &ident[..4] != b"\x7fELF" {
+        return Err("elf error: not an ELF file (bad magic)".into());
+    }
+    if ident[4] != 1 {
+        return Err("elf error: only ELF32 (RV32) binaries are supported".into());
+    }
+    if ident[5] != 1 {
+        return Err("elf error: only little-endian binaries are supported".into());
+    }
+    let e_type = r.u16(0x10, "header")?;
+    if e_type != ET_EXEC {
+        return Err(format!("elf error: expected an executable (ET_EXEC), got e_type {e_type}"));
+    }
+    let machine = r.u16(0x12, "header")?;
+    if machine != EM_RISCV {
+        return Err(format!("elf error: not a RISC-V binary (e_machine {machine:#x})"));
+    }
+    let entry = r.u32(0x18, "header")?;
+    let phoff = r.u32(0x1c, "header")? as usize;
+    let phentsize = r.u16(0x2a, "header")? as usize;
+    let phnum = r.u16(0x2c, "header")? as usize;
+
+    let mut segments = Vec::new();
+    for i in 0..phnum {
+        let ph = r.table_entry(phoff, i, phentsize, "program header")?;
+        if r.u32(ph, "program header")? != PT_LOAD {
+            continue;
+        }
+        let offset = r.u32(ph + 0x04, "program header")? as usize;
+        let vaddr = r.u32(ph + 0x08, "program header")?;
+        let filesz = r.u32(ph + 0x10, "program header")? as usize;
+        let flags = r.u32(ph + 0x18, "program header")?;
+        segments.push(Segment {
+            vaddr,
+            data: r.slice(offset, filesz, "segment")?,
+            exec: flags & PF_X != 0,
+        });
+    }
+
+    // ROM range: executable sections if known, otherwise whole executable segments.
+    let sections = alloc_sections(&r)?;
+    let mut text_ranges: Vec<(u32, u32)> =
+        sections.iter().filter(|s| s.exec).map(|s| (s.start, s.end)).collect();
+    if text_ranges.is_empty() {
+        text_ranges = segments
+            .iter()
+            .filter(|s| s.exec && !s.data.is_empty())
+            .map(|s| (s.vaddr, s.vaddr.wrapping_add(s.data.len() as u32)))
+            .collect();
+    }
+    let text_start = text_ranges.iter().map(|&(start, _)| start).min();
+    let text_end = text_ranges.iter().map(|&(_, end)| end).max();
+    let (Some(text_start), Some(text_end)) = (text_start, text_end) else {
+        return Err("elf error: no executable code found".into());
+    };
+    if text_start & 3 != 0 {
+        return Err(format!("elf error: text starts at unaligned address {text_start:#x}"));
+    }
+    if !(text_start..text_end).contains(&entry) {
+        return Err(format!(
+            "elf error: entry point {entry:#x} is outside the text [{text_start:#x}, {text_end:#x})"
+        ));
+    }
+    if entry & 3 != 0 {
+        return Err(format!("elf error: entry point {entry:#x} is not 4-byte aligned"));
+    }
+    let text_end = text_start + (text_end - text_start).next_multiple_of(4);
+
+    // Where the `jal` trampoline goes when the entry point is not the first text word.
+    let trampoline = if entry == text_start {
+        None
+    } else {
+        let at = text_start.checked_sub(4).ok_or_else(|| {
+            format!(
+                "elf error: entry point {entry:#x} is not the first text word and there is no room for a \
+                 trampoline below {text_start:#x}"
+            )
+        })?;
+        if entry - at > JAL_MAX_OFFSET {
+            return Err(format!(
+                "elf error: entry point {entry:#x} is out of jal range of the trampoline at {at:#x} \
+                 (at most {JAL_MAX_OFFSET:#x} bytes)"
+            ));
+        }
+        Some(at)
+    };
+    let in_text = |addr: u32| text_ranges.iter().any(|&(start, end)| (start..end).contains(&addr));
+    let mut text: Vec<u8> = std::iter::repeat_n(NOP.to_le_bytes(), ((text_end - text_start) / 4) as usize)
+        .flatten()
+        .collect();
+    let mut ram_bytes = std::collections::BTreeMap::new();
+    for seg in &segments {
+        for (i, &byte) in seg.data.iter().enumerate() {
+            let addr = seg.vaddr.wrapping_add(i as u32);
+            let in_section = sections.is_empty() || sections.iter().any(|s| (s.start..s.end).contains(&addr));
+            if seg.exec && in_text(addr) {
+                text[(addr - text_start) as usize] = byte;
+            } else if in_section {
+                if trampoline.is_some_and(|at| (at..text_start).contains(&addr)) {
+                    return Err(format!(
+                        "elf error: entry point {entry:#x} needs a trampoline at {:#x}, but data is loaded there",
+                        text_start - 4
+                    ));
+                }
+                if byte != 0 {
+                    ram_bytes.insert(addr, byte);
+                }
+            }
+        }
+    }
+
+    for (i, chunk) in text.chunks_exact(4).enumerate() {
+        let addr = text_start + 4 * i as u32;
+        let word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        if word & 3 != 3 {
+            return Err(format!(
+                "elf error: compressed instruction {:#06x} at {addr:#x} (build for riscv32im without the C extension)",
+                word & 0xffff
+            ));
+        }
+        match decode_instruction(word) {
+            Ok(instr) if rv32_supported(&instr) => {}
+            Ok(instr) => {
+                return Err(format!("elf error: unsupported instruction {word:#010x} ({instr:?}) at {addr:#x}"))
+            }
+            Err(e) => return Err(format!("elf error: invalid instruction {word:#010x} at {addr:#x}: {e}")),
+        }
+    }
+
+    let (program_base, rom) = match trampoline {
+        None => (text_start, text),
+        Some(at) => {
+            let jump = RiscvInstruction::Jal {
+                rd: 0,
+                imm: (entry - at) as i32,
+            };
+            let mut rom = encode_program(&[jump]);
+            rom.extend_from_slice(&text);
+            (at, rom)
+        }
+    };
+
+    // Group RAM bytes into aligned little-endian words.
+    let mut ram_init: Vec<(u32, u32)> = Vec::new();
+    for (addr, byte) in ram_bytes {
+        let word_addr = addr & !3;
+        let shift = 8 * (addr & 3);
+        match ram_init.last_mut() {
+            Some((a, value)) if *a == word_addr => *value |= (byte as u32) << shift,
+            _ => ram_init.push((word_addr, (byte as u32) << shift)),
+        }
+    }
+
+    Ok(Rv32ElfImage {
+        program_base,
+        entry,
+        program: Rv32Program {
+            rom,
+            ram_init,
+            ..Default::default()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDI_A0_7: u32 = 0x0070_0513;
+    const ECALL: u32 = 0x0000_0073;
+
+    fn words(ws: &[u32]) -> Vec<u8> {
+        ws.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    /// Minimal ELF32 executable: one `PT_LOAD` per `(vaddr, bytes, flags)` segment and, when
+    /// `sections` is non-empty, a section header table with one `PROGBITS` section per
+    /// `(addr, size, flags)` after the null section.
+    fn elf(entry: u32, segments: &[(u32, Vec<u8>, u32)], sections: &[(u32, u32, u32)]) -> Vec<u8> {
+        let phoff = 52;
+        let mut data_off = phoff + 32 * segments.len();
+        let mut out = vec![0u8; data_off];
+        out[..7].copy_from_slice(b"\x7fELF\x01\x01\x01");
+        let put16 = |out: &mut Vec<u8>, off: usize, v: u16| out[off..off + 2].copy_from_slice(&v.to_le_bytes());
+        let put32 = |out: &mut Vec<u8>, off: usize, v: u32| out[off..off + 4].copy_from_slice(&v.to_le_bytes());
+        put16(&mut out, 0x10, ET_EXEC);
+        put16(&mut out, 0x12, EM_RISCV);
+        put32(&mut out, 0x14, 1);
+        put32(&mut out, 0x18, entry);
+        put32(&mut out, 0x1c, phoff as u32);
+        put16(&mut out, 0x28, 52);
+        put16(&mut out, 0x2a, 32);
+        put16(&mut out, 0x2c, segments.len() as u16);
+        for (i, (vaddr, bytes, flags)) in segments.iter().enumerate() {
+            let ph = phoff + 32 * i;
+            put32(&mut out, ph, PT_LOAD);
+            put32(&mut out, ph + 0x04, data_off as u32);
+            put32(&mut out, ph + 0x08, *vaddr);
+            put32(&mut out, ph + 0x0c, *vaddr);
+            put32(&mut out, ph + 0x10, bytes.len() as u32);
+            put32(&mut out, ph + 0x14, bytes.len() as u32);
+            put32(&mut out, ph + 0x18, *flags);
+            out.extend_from_slice(bytes);
+            data_off += bytes.len();
+        }
+        if !sections.is_empty() {
+            let shoff = out.len();
+            out.resize(shoff + 40 * (sections.len() + 1), 0);
+            put32(&mut out, 0x20, shoff as u32);
+            put16(&mut out, 0x2e, 40);
+            put16(&mut out, 0x30, sections.len() as u16 + 1);
+            for (i, &(addr, size, flags)) in sections.iter().enumerate() {
+                let sh = shoff + 40 * (i + 1);
+                put32(&mut out, sh + 0x04, SHT_PROGBITS);
+                put32(&mut out, sh + 0x08, SHF_ALLOC | flags);
+                put32(&mut out, sh + 0x0c, addr);
+                put32(&mut out, sh + 0x14, size);
+            }
+        }
+        out
+    }
+
+    fn rom_words(image: &Rv32ElfImage) -> Vec<u32> {
+        image
+            .program
+            .rom
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    /// `.text` at `0x1000`, `.rodata` at `0x1008`, an unused word, then `.text.b` at `0x1010`,
+    /// all in one executable segment.
+    fn split_text(entry: u32) -> Vec<u8> {
+        let segment = words(&[ADDI_A0_7, ECALL, 0xdead_beef, 0, ECALL]);
+        let sections = [
+            (0x1000, 8, SHF_EXECINSTR),
+            (0x1008, 4, 0),
+            (0x1010, 4, SHF_EXECINSTR),
+        ];
+        elf(entry, &[(0x1000, segment, PF_X)], &sections)
+    }
+
+    #[test]
+    fn rom_holds_only_executable_bytes() {
+        let image = load_rv32_elf(&split_text(0x1000)).unwrap();
+        assert_eq!((image.program_base, image.entry), (0x1000, 0x1000));
+        assert_eq!(rom_words(&image), [ADDI_A0_7, ECALL, NOP, NOP, ECALL]);
+        assert_eq!(image.program.ram_init, [(0x1008, 0xdead_beef)]);
+    }
+
+    #[test]
+    fn entry_past_the_first_word_gets_a_trampoline() {
+        let image = load_rv32_elf(&split_text(0x1010)).unwrap();
+        assert_eq!(image.program_base, 0xffc);
+        let rom = rom_words(&image);
+        assert!(matches!(decode_instruction(rom[0]), Ok(RiscvInstruction::Jal { rd: 0, imm: 0x14 })));
+        assert_eq!(rom[1..], [ADDI_A0_7, ECALL, NOP, NOP, ECALL]);
+
+        let at_zero = elf(4, &[(0, words(&[ADDI_A0_7, ECALL]), PF_X)], &[]);
+        let err = load_rv32_elf(&at_zero).err().unwrap();
+        assert!(err.contains("no room for a trampoline"), "{err}");
+
+        let far = elf(
+            0x20_0000,
+            &[(0x1000, words(&[ECALL]), PF_X), (0x20_0000, words(&[ADDI_A0_7, ECALL]), PF_X)],
+            &[],
+        );
+        let err = load_rv32_elf(&far).err().unwrap();
+        assert!(err.contains("out of jal range"), "{err}");
+    }
+
+    #[test]
+    fn malformed_elves_are_errors() {
+        let good = split_text(0x1000);
+        for (at, byte, what) in [(0, 0, "bad magic"), (4, 2, "ELF32"), (0x12, 0x3e, "not a RISC-V")] {
+            let mut bad = good.clone();
+            bad[at] = byte;
+            let err = load_rv32_elf(&bad).err().unwrap();
+            assert!(err.contains(what), "{err}");
+        }
+        let err = load_rv32_elf(&good[..40]).err().unwrap();
+        assert!(err.contains("truncated"), "{err}");
+        let compressed = elf(0x1000, &[(0x1000, words(&[0x4505_0001]), PF_X)], &[]);
+        assert!(load_rv32_elf(&compressed).err().unwrap().contains("compressed instruction"));
+    }
+}
@@ -0,0 +1,602 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+
+use deprecated_neo_memory::riscv::lookups::{
+    decode_instruction, BranchCondition, RiscvInstruction, RiscvMemOp, RiscvOpcode,
+};
+use tsify::Tsify;
+
+use crate::rv32_syscall::{Rv32DebugPrint, Rv32IoReport, Rv32SyscallShim, SAVE_AREA};
+
+/// Register read performed by one instruction.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32RegRead {
+    pub(crate) reg: u8,
+    pub(crate) value: u32,
+}
+
+/// Register write performed by one instruction (writes to `x0` are not recorded).
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32RegWrite {
+    pub(crate) reg: u8,
+    pub(crate) value: u32,
+}
+
+/// RAM access performed by one load/store. `value` is the zero-extended `width`-byte value.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32MemAccess {
+    pub(crate) addr: u32,
+    pub(crate) width: u8,
+    pub(crate) value: u32,
+    pub(crate) write: bool,
+}
+
+/// One executed instruction.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct Rv32StepRecord {
+    pub(crate) step: usize,
+    pub(crate) pc: u32,
+    pub(crate) word: u32,
+    pub(crate) reg_reads: Vec<Rv32RegRead>,
+    #[tsify(optional)]
+    pub(crate) reg_write: Option<Rv32RegWrite>,
+    #[tsify(optional)]
+    pub(crate) mem: Option<Rv32MemAccess>,
+    pub(crate) next_pc: u32,
+}
+
+/// A page of executed instructions.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct Rv32StepRecords(pub(crate) Vec<Rv32StepRecord>);
+
+/// Why execution stopped.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, Tsify)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Rv32HaltReason {
+    Ecall,
+    MaxSteps,
+    InvalidPc { pc: u32 },
+    Unsupported { pc: u32, word: u32 },
+    /// A load/store touched bytes outside every RAM region.
+    OutOfBounds { pc: u32, addr: u32, width: u8, write: bool },
+    /// The `ecall` at `pc` had an `a7` the syscall ABI doesn't define.
+    UnknownSyscall { pc: u32, number: u32 },
+    /// The `SYS_COMMIT` at `pc` found the journal full.
+    JournalFull { pc: u32 },
+}
+
+impl fmt::Display for Rv32HaltReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rv32HaltReason::Ecall => write!(f, "ecall"),
+            Rv32HaltReason::MaxSteps => write!(f, "max_steps reached"),
+            Rv32HaltReason::InvalidPc { pc } => write!(f, "invalid pc {pc:#x}"),
+            Rv32HaltReason::Unsupported { pc, word } => {
+                write!(f, "unsupported instruction {word:#010x} at pc {pc:#x}")
+            }
+            Rv32HaltReason::OutOfBounds { pc, addr, width, write } => write!(
+                f,
+                "out-of-bounds {} of {width} byte(s) at {addr:#x} by pc {pc:#x} (outside mapped RAM)",
+                if *write { "store" } else { "load" }
+            ),
+            Rv32HaltReason::UnknownSyscall { pc, number } => write!(f, "unknown syscall {number} (a7) at pc {pc:#x}"),
+            Rv32HaltReason::JournalFull { pc } => write!(f, "journal full at SYS_COMMIT, pc {pc:#x}"),
+        }
+    }
+}
+
+/// Final value of a RAM word that a load or store touched.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32MemWord {
+    pub(crate) addr: u32,
+    pub(crate) value: u32,
+}
+
+/// Outcome of a native run, for sizing `chunk_rows`/`max_steps` before proving.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct Rv32ExecReport {
+    pub(crate) steps: usize,
+    pub(crate) halt: Rv32HaltReason,
+    pub(crate) final_pc: u32,
+    pub(crate) regs: Vec<u32>,
+    /// Every word touched by a load/store, with its final value, sorted by address.
+    pub(crate) memory: Vec<Rv32MemWord>,
+    /// Predicted prover trace length: one row per executed instruction, including the `ecall`.
+    pub(crate) trace_len: usize,
+    /// Predicted fold count, `trace_len / chunk_rows` rounded up (absent when `chunk_rows` is 0).
+    #[tsify(optional)]
+    pub(crate) folds: Option<usize>,
+    /// Exit code, journal and debug prints, for guests built with `syscalls`.
+    #[tsify(optional)]
+    pub(crate) io: Option<Rv32IoReport>,
+}
+
+impl Rv32ExecReport {
+    pub(crate) fn new(machine: &Rv32Machine, trace: &[Rv32StepRecord], chunk_rows: usize) -> Self {
+        let touched: BTreeSet<u32> = trace
+            .iter()
+            .filter_map(|rec| rec.mem.as_ref())
+            .flat_map(|m| (0..m.width as u32).map(move |i| m.addr.wrapping_add(i) & !3))
+            .collect();
+        let memory = touched
+            .into_iter()
+            .map(|addr| Rv32MemWord {
+                addr,
+                value: machine.read_mem(addr, 4),
+            })
+            .collect();
+        Rv32ExecReport {
+            steps: machine.steps,
+            halt: machine.halted.clone().unwrap_or(Rv32HaltReason::MaxSteps),
+            final_pc: machine.pc,
+            regs: machine.regs.to_vec(),
+            memory,
+            trace_len: trace.len(),
+            folds: (chunk_rows > 0).then(|| trace.len().div_ceil(chunk_rows)),
+            io: machine.io_report(),
+        }
+    }
+}
+
+/// Native RV32 interpreter over the ROM/RAM split used by `Rv32TraceWiring`:
+/// instructions are fetched from a read-only ROM at `program_base`, loads/stores go to a sparse,
+/// byte-addressed RAM.
+pub(crate) struct Rv32Machine {
+    program_base: u32,
+    words: Vec<u32>,
+    program: Vec<RiscvInstruction>,
+    pub(crate) regs: [u32; 32],
+    pub(crate) pc: u32,
+    pub(crate) ram: BTreeMap<u32, u8>,
+    /// Address ranges loads/stores must stay within; `None` leaves the whole address space usable.
+    ram_regions: Option<Vec<Range<u64>>>,
+    /// Linked syscall shim, whose entry points record prints and stop on ABI errors.
+    syscalls: Option<Rv32SyscallShim>,
+    pub(crate) prints: Vec<Rv32DebugPrint>,
+    pub(crate) steps: usize,
+    pub(crate) halted: Option<Rv32HaltReason>,
+}
+
+fn alu(op: RiscvOpcode, a: u32, b: u32) -> Option<u32> {
+    let (sa, sb) = (a as i32, b as i32);
+    let shamt = b & 31;
+    Some(match op {
+        RiscvOpcode::Add => a.wrapping_add(b),
+        RiscvOpcode::Sub => a.wrapping_sub(b),
+        RiscvOpcode::And => a & b,
+        RiscvOpcode::Or => a | b,
+        RiscvOpcode::Xor => a ^ b,
+        RiscvOpcode::Sll => a << shamt,
+        RiscvOpcode::Srl => a >> shamt,
+        RiscvOpcode::Sra => (sa >> shamt) as u32,
+        RiscvOpcode::Slt => (sa < sb) as u32,
+        RiscvOpcode::Sltu => (a < b) as u32,
+        RiscvOpcode::Mul => a.wrapping_mul(b),
+        RiscvOpcode::Mulh => ((sa as i64 * sb as i64) >> 32) as u32,
+        RiscvOpcode::Mulhu => ((a as u64 * b as u64) >> 32) as u32,
+        RiscvOpcode::Mulhsu => ((sa as i64 * b as i64) >> 32) as u32,
+        RiscvOpcode::Div => match (sa, sb) {
+            (_, 0) => u32::MAX,
+            (i32::MIN, -1) => a,
+            _ => (sa / sb) as u32,
+        },
+        RiscvOpcode::Divu => a.checked_div(b).unwrap_or(u32::MAX),
+        RiscvOpcode::Rem => match (sa, sb) {
+            (_, 0) => a,
+            (i32::MIN, -1) => 0,
+            _ => (sa % sb) as u32,
+        },
+        RiscvOpcode::Remu => a.checked_rem(b).unwrap_or(a),
+        _ => return None,
+    })
+}
+
+fn branch_taken(cond: BranchCondition, a: u32, b: u32) -> bool {
+    match cond {
+        BranchCondition::Eq => a == b,
+        BranchCondition::Ne => a != b,
+        BranchCondition::Lt => (a as i32) < (b as i32),
+        BranchCondition::Ge => (a as i32) >= (b as i32),
+        BranchCondition::Ltu => a < b,
+        BranchCondition::Geu => a >= b,
+    }
+}
+
+/// Whether the interpreter (and the RV32 trace) can execute `instr`.
+pub(crate) fn rv32_supported(instr: &RiscvInstruction) -> bool {
+    match instr {
+        RiscvInstruction::RAlu { op, .. } | RiscvInstruction::IAlu { op, .. } => alu(*op, 0, 1).is_some(),
+        RiscvInstruction::Load { op, .. } | RiscvInstruction::Store { op, .. } => mem_width(*op).is_some(),
+        RiscvInstruction::Branch { .. }
+        | RiscvInstruction::Jal { .. }
+        | RiscvInstruction::Jalr { .. }
+        | RiscvInstruction::Lui { .. }
+        | RiscvInstruction::Auipc { .. }
+        | RiscvInstruction::Nop
+        | RiscvInstruction::Halt => true,
+        _ => false,
+    }
+}
+
+/// `(width in bytes, sign-extend)` for a load/store op.
+fn mem_width(op: RiscvMemOp) -> Option<(u8, bool)> {
+    Some(match op {
+        RiscvMemOp::Lb => (1, true),
+        RiscvMemOp::Lbu | RiscvMemOp::Sb => (1, false),
+        RiscvMemOp::Lh => (2, true),
+        RiscvMemOp::Lhu | RiscvMemOp::Sh => (2, false),
+        RiscvMemOp::Lw | RiscvMemOp::Sw => (4, false),
+        _ => return None,
+    })
+}
+
+impl Rv32Machine {
+    /// Decode `rom` (little-endian words) at `program_base` and apply `ram_init` word writes.
+    pub(crate) fn new(program_base: u32, rom: &[u8], ram_init: &[(u32, u32)]) -> Result<Self, String> {
+        if rom.len() & 3 != 0 {
+            return Err(format!("program length {} is not a multiple of 4 bytes", rom.len()));
+        }
+        let words = rom
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect::<Vec<_>>();
+        let program = words
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| {
+                decode_instruction(w).map_err(|e| {
+                    format!(
+                        "invalid instruction {w:#010x} at pc {:#x}: {e}",
+                        program_base.wrapping_add(4 * i as u32)
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut machine = Rv32Machine {
+            program_base,
+            words,
+            program,
+            regs: [0; 32],
+            pc: program_base,
+            ram: BTreeMap::new(),
+            ram_regions: None,
+            syscalls: None,
+            prints: Vec::new(),
+            steps: 0,
+            halted: None,
+        };
+        for &(addr, value) in ram_init {
+            machine.write_mem(addr, 4, value);
+        }
+        Ok(machine)
+    }
+
+    /// Halt loads/stores that fall outside every range of `ram_regions` with `OutOfBounds`.
+    pub(crate) fn with_ram_regions(mut self, ram_regions: Option<Vec<Range<u64>>>) -> Self {
+        self.ram_regions = ram_regions;
+        self
+    }
+
+    /// Service syscalls through `shim`, which must be linked into the program.
+    pub(crate) fn with_syscalls(mut self, shim: Option<Rv32SyscallShim>) -> Self {
+        self.syscalls = shim;
+        self
+    }
+
+    pub(crate) fn io_report(&self) -> Option<Rv32IoReport> {
+        self.syscalls.map(|shim| shim.io.report(self))
+    }
+
+    pub(crate) fn read_mem(&self, addr: u32, width: u8) -> u32 {
+        (0..width as u32).fold(0u32, |acc, i| {
+            let byte = self.ram.get(&addr.wrapping_add(i)).copied().unwrap_or(0);
+            acc | ((byte as u32) << (8 * i))
+        })
+    }
+
+    /// `len` bytes starting at `addr`, wrapping around the address space.
+    pub(crate) fn read_bytes(&self, addr: u32, len: u32) -> Vec<u8> {
+        (0..len)
+            .map(|i| self.ram.get(&addr.wrapping_add(i)).copied().unwrap_or(0))
+            .collect()
+    }
+
+    fn write_mem(&mut self, addr: u32, width: u8, value: u32) {
+        for i in 0..width as u32 {
+            self.ram.insert(addr.wrapping_add(i), (value >> (8 * i)) as u8);
+        }
+    }
+
+    fn fetch_index(&self, pc: u32) -> Option<usize> {
+        let off = pc.checked_sub(self.program_base)?;
+        if off & 3 != 0 {
+            return None;
+        }
+        let idx = (off / 4) as usize;
+        (idx < self.program.len()).then_some(idx)
+    }
+
+    /// Execute one instruction. Returns `None` once the machine has halted.
+    pub(crate) fn step(&mut self) -> Option<Rv32StepRecord> {
+        if self.halted.is_some() {
+            return None;
+        }
+        let pc = self.pc;
+        if let Some(shim) = self.syscalls {
+            if pc == shim.print_pc {
+                self.prints.push(Rv32DebugPrint {
+                    step: self.steps,
+                    pc: shim.caller(self),
+                    value: self.regs[10],
+                });
+            } else if pc == shim.unknown_pc {
+                self.halted = Some(Rv32HaltReason::UnknownSyscall {
+                    pc: shim.caller(self),
+                    number: self.regs[17],
+                });
+                return None;
+            } else if pc == shim.journal_full_pc {
+                self.halted = Some(Rv32HaltReason::JournalFull { pc: shim.caller(self) });
+                return None;
+            }
+        }
+        let Some(idx) = self.fetch_index(pc) else {
+            self.halted = Some(Rv32HaltReason::InvalidPc { pc });
+            return None;
+        };
+        let word = self.words[idx];
+        let instr = self.program[idx].clone();
+
+        let mut reg_reads = Vec::new();
+        let mut read = |regs: &[u32; 32], r: u8| {
+            let value = regs[r as usize];
+            reg_reads.push(Rv32RegRead { reg: r, value });
+            value
+        };
+        let mut rd_value: Option<(u8, u32)> = None;
+        let mut mem = None;
+        let mut next_pc = pc.wrapping_add(4);
+
+        match instr {
+            RiscvInstruction::RAlu { op, rd, rs1, rs2 } => {
+                let a = read(&self.regs, rs1);
+                let b = read(&self.regs, rs2);
+                match alu(op, a, b) {
+                    Some(v) => rd_value = Some((rd, v)),
+                    None => return self.unsupported(pc, word),
+                }
+            }
+            RiscvInstruction::IAlu { op, rd, rs1, imm } => {
+                let a = read(&self.regs, rs1);
+                match alu(op, a, imm as u32) {
+                    Some(v) => rd_value = Some((rd, v)),
+                    None => return self.unsupported(pc, word),
+                }
+            }
+            RiscvInstruction::Load { op, rd, rs1, imm } => {
+                let Some((width, signed)) = mem_width(op) else {
+                    return self.unsupported(pc, word);
+                };
+                let addr = read(&self.regs, rs1).wrapping_add(imm as u32);
+                if !self.in_ram(addr, width) {
+                    return self.out_of_bounds(pc, addr, width, false);
+                }
+                let raw = self.read_mem(addr, width);
+                let shift = 32 - 8 * width as u32;
+                let value = if signed && width < 4 {
+                    (((raw << shift) as i32) >> shift) as u32
+                } else {
+                    raw
+                };
+                mem = Some(Rv32MemAccess {
+                    addr,
+                    width,
+                    value: raw,
+                    write: false,
+                });
+                rd_value = Some((rd, value));
+            }
+            RiscvInstruction::Store { op, rs1, rs2, imm } => {
+                let Some((width, _)) = mem_width(op) else {
+                    return self.unsupported(pc, word);
+                };
+                let addr = read(&self.regs, rs1).wrapping_add(imm as u32);
+                let value = read(&self.regs, rs2);
+                let value = if width < 4 { value & ((1u32 << (8 * width)) - 1) } else { value };
+                if !self.in_ram(addr, width) {
+                    return self.out_of_bounds(pc, addr, width, true);
+                }
+                self.write_mem(addr, width, value);
+                mem = Some(Rv32MemAccess {
+                    addr,
+                    width,
+                    value,
+                    write: true,
+                });
+            }
+            RiscvInstruction::Branch { cond, rs1, rs2, imm } => {
+                let a = read(&self.regs, rs1);
+                let b = read(&self.regs, rs2);
+                if branch_taken(cond, a, b) {
+                    next_pc = pc.wrapping_add(imm as u32);
+                }
+            }
+            RiscvInstruction::Jal { rd, imm } => {
+                rd_value = Some((rd, pc.wrapping_add(4)));
+                next_pc = pc.wrapping_add(imm as u32);
+            }
+            RiscvInstruction::Jalr { rd, rs1, imm } => {
+                let base = read(&self.regs, rs1);
+                rd_value = Some((rd, pc.wrapping_add(4)));
+                next_pc = base.wrapping_add(imm as u32) & !1;
+            }
+            // The upper immediate is taken from the word itself rather than the decoded `imm`.
+            RiscvInstruction::Lui { rd, .. } => {
+                rd_value = Some((rd, word & 0xffff_f000));
+            }
+            RiscvInstruction::Auipc { rd, .. } => {
+                rd_value = Some((rd, pc.wrapping_add(word & 0xffff_f000)));
+            }
+            RiscvInstruction::Nop => {}
+            RiscvInstruction::Halt => {
+                self.halted = Some(Rv32HaltReason::Ecall);
+                next_pc = pc;
+            }
+            _ => return self.unsupported(pc, word),
+        }
+
+        let reg_write = match rd_value {
+            Some((rd, value)) if rd != 0 => {
+                self.regs[rd as usize] = value;
+                Some(Rv32RegWrite { reg: rd, value })
+            }
+            _ => None,
+        };
+
+        let record = Rv32StepRecord {
+            step: self.steps,
+            pc,
+            word,
+            reg_reads,
+            reg_write,
+            mem,
+            next_pc,
+        };
+        self.steps += 1;
+        self.pc = next_pc;
+        Some(record)
+    }
+
+    fn unsupported(&mut self, pc: u32, word: u32) -> Option<Rv32StepRecord> {
+        self.halted = Some(Rv32HaltReason::Unsupported { pc, word });
+        None
+    }
+
+    fn in_ram(&self, addr: u32, width: u8) -> bool {
+        let (start, end) = (addr as u64, addr as u64 + width as u64);
+        if self.syscalls.is_some() && start >= SAVE_AREA as u64 && end <= 1 << 32 {
+            return true;
+        }
+        self.ram_regions
+            .as_ref()
+            .is_none_or(|regions| regions.iter().any(|r| r.start <= start && end <= r.end))
+    }
+
+    fn out_of_bounds(&mut self, pc: u32, addr: u32, width: u8, write: bool) -> Option<Rv32StepRecord> {
+        self.halted = Some(Rv32HaltReason::OutOfBounds { pc, addr, width, write });
+        None
+    }
+
+    /// Run until halt or `max_steps` executed instructions, collecting every step record.
+    pub(crate) fn run(&mut self, max_steps: usize) -> Vec<Rv32StepRecord> {
+        let mut trace = Vec::new();
+        while self.halted.is_none() {
+            if self.steps >= max_steps {
+                self.halted = Some(Rv32HaltReason::MaxSteps);
+                break;
+            }
+            if let Some(rec) = self.step() {
+                trace.push(rec);
+            }
+        }
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::riscv_asm::{assemble_rv32, Rv32AsmLayout};
+
+    const INT_MIN: u32 = 0x8000_0000;
+
+    #[test]
+    fn division_corner_cases() {
+        let neg = |v: i32| v as u32;
+        let cases = [
+            (RiscvOpcode::Div, neg(-100), 7, neg(-14)),
+            (RiscvOpcode::Divu, neg(-100), 7, 0x2492_4916),
+            (RiscvOpcode::Rem, neg(-100), 7, neg(-2)),
+            (RiscvOpcode::Remu, neg(-100), 7, 2),
+            (RiscvOpcode::Div, 7, neg(-2), neg(-3)),
+            (RiscvOpcode::Rem, 7, neg(-2), 1),
+            // Division by zero: all-ones quotient, remainder is the dividend.
+            (RiscvOpcode::Div, neg(-100), 0, u32::MAX),
+            (RiscvOpcode::Divu, neg(-100), 0, u32::MAX),
+            (RiscvOpcode::Rem, neg(-100), 0, neg(-100)),
+            (RiscvOpcode::Remu, neg(-100), 0, neg(-100)),
+            // Signed overflow: INT_MIN / -1 = INT_MIN, remainder 0.
+            (RiscvOpcode::Div, INT_MIN, u32::MAX, INT_MIN),
+            (RiscvOpcode::Rem, INT_MIN, u32::MAX, 0),
+            (RiscvOpcode::Divu, INT_MIN, u32::MAX, 0),
+            (RiscvOpcode::Remu, INT_MIN, u32::MAX, INT_MIN),
+        ];
+        for (op, a, b, expected) in cases {
+            assert_eq!(alu(op, a, b), Some(expected), "{op:?} {a:#x}, {b:#x}");
+        }
+    }
+
+    fn machine(src: &str) -> Rv32Machine {
+        let out = assemble_rv32(src, &HashMap::new(), &Rv32AsmLayout::default());
+        let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        Rv32Machine::new(0, &program.rom, &program.ram_init).unwrap()
+    }
+
+    #[test]
+    fn exec_report_predicts_the_trace() {
+        let src = "
+    li   t0, 0
+    li   t1, 5
+loop:
+    addi t0, t0, 1
+    blt  t0, t1, loop
+    lui  a0, 0x12345
+    auipc a1, 0xfffff
+    sw   t0, 0x100(x0)
+    ecall
+";
+        let mut m = machine(src);
+        let trace = m.run(1000);
+        let report = Rv32ExecReport::new(&m, &trace, 4);
+        assert_eq!(report.halt, Rv32HaltReason::Ecall);
+        assert_eq!((report.steps, report.trace_len, report.folds), (16, 16, Some(4)));
+        assert_eq!(report.final_pc, 0x1c);
+        assert_eq!((report.regs[5], report.regs[10], report.regs[11]), (5, 0x1234_5000, 0xffff_f014));
+        assert_eq!(report.memory.iter().map(|w| (w.addr, w.value)).collect::<Vec<_>>(), [(0x100, 5)]);
+        assert!(report.io.is_none());
+
+        let mut m = machine("spin:\n    j spin\n");
+        let trace = m.run(10);
+        let report = Rv32ExecReport::new(&m, &trace, 0);
+        assert_eq!((report.halt, report.steps, report.folds), (Rv32HaltReason::MaxSteps, 10, None));
+
+        let mut m = machine("    li t0, 0x100\n    jr t0\n");
+        let trace = m.run(10);
+        let report = Rv32ExecReport::new(&m, &trace, 4);
+        assert_eq!(report.halt, Rv32HaltReason::InvalidPc { pc: 0x100 });
+        assert_eq!(report.trace_len, 2);
+    }
+
+    /// The values listed in `web/examples/rv32m_div.asm`.
+    #[test]
+    fn rv32m_div_example() {
+        let src = include_str!("../../web/examples/rv32m_div.asm");
+        let out = assemble_rv32(src, &HashMap::new(), &Rv32AsmLayout::default());
+        let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        let mut ram_init = program.ram_init.clone();
+        ram_init.extend([(0x200, 0xffff_ff9c), (0x204, 7), (0x208, INT_MIN)]);
+        let mut machine = Rv32Machine::new(0, &program.rom, &ram_init).unwrap();
+        machine.run(1000);
+        assert_eq!(machine.halted, Some(Rv32HaltReason::Ecall));
+
+        let expected = [0xffff_fff2, 0x2492_4916, 0xffff_fffe, 2, u32::MAX, 0xffff_ff9c, INT_MIN, 0];
+        for (i, want) in expected.into_iter().enumerate() {
+            let addr = 0x100 + 4 * i as u32;
+            assert_eq!(machine.read_mem(addr, 4), want, "word at {addr:#x}");
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Guest syscall ABI for assembled RV32 programs.
+//!
+//! The trace-wiring prover executes `ecall` as `Halt`, so a syscall cannot trap into the host.
+//! With `syscalls` enabled the assembler instead rewrites every `ecall` into `sw t6, -4(x0);
+//! jal t6, __neo_syscall; lw t6, -4(x0)` and links `SHIM`, which services the call with
+//! ordinary loads/stores on an I/O block in RAM. The shim runs inside the proven trace, so what
+//! the guest reads and commits is constrained like any other memory access.
+//!
+//! `a7` selects the call:
+//! - `SYS_HALT` (0): halt with exit code `a0`
+//! - `SYS_READ` (1): `a0` = next input word and `a1` = 1, or `a0` = `a1` = 0 once input runs out
+//! - `SYS_COMMIT` (2): append `a0` to the journal
+//! - `SYS_PRINT` (3): debug-print `a0` (native runs only; not part of the proof)
+//!
+//! Only `SYS_READ` writes registers (`a0`/`a1`); every other register, `t6` included, is
+//! preserved. The shim keeps what it spills in `SAVE_AREA`, the last 16 bytes of the address
+//! space, which `x0`-relative loads and stores reach without a base register: `-4` the caller's
+//! `t6`, `-8` the return address, `-12` the caller's `a0`.
+//!
+//! What the statement binds is the exit code and the journal. The input stream is left out of it
+//! and out of everything exported (`Rv32Proof` has no byte encoding), but it is not private from
+//! the verifier: checking a statement rebuilds the initial RAM, input included, from the
+//! verifier's own config. Proving a statement to someone who doesn't know the input is not
+//! supported until `neo-fold` can verify a run whose initial RAM is partly hidden.
+//!
+//! The I/O header is initialized from the layout alone; initial RAM words from `ram_init` or
+//! `inputs` inside the block are rejected, so the journal and its cursor start out empty.
+//!
+//! I/O block at `__neo_io`, one word per field:
+//! `+0` input cursor, `+4` input end, `+8` journal cursor, `+12` exit code, `+16`/`+20`
+//! reserved, `+24` journal end, `+28` reserved; then `journal_words` journal words, then the
+//! input.
+
+use std::ops::Range;
+
+use tsify::Tsify;
+
+use crate::riscv_asm::{Rv32EcallShim, Rv32Program};
+use crate::rv32::Rv32OutputClaim;
+use crate::rv32_exec::Rv32Machine;
+
+pub(crate) const SYS_HALT: u32 = 0;
+pub(crate) const SYS_READ: u32 = 1;
+pub(crate) const SYS_COMMIT: u32 = 2;
+pub(crate) const SYS_PRINT: u32 = 3;
+
+/// Where the I/O block goes when the memory map declares no `io` region.
+pub(crate) const DEFAULT_IO_BASE: u32 = 0x1_0000;
+
+/// Shim register save area, outside any memory map; the interpreter always lets syscall-enabled
+/// guests touch it.
+pub(crate) const SAVE_AREA: u32 = 0xffff_fff0;
+const SAVED_T6: i32 = -4;
+const SAVED_LINK: i32 = -8;
+
+const HEADER_BYTES: u32 = 32;
+const INPUT_CURSOR: u32 = 0;
+const INPUT_END: u32 = 4;
+const JOURNAL_CURSOR: u32 = 8;
+const EXIT_CODE: u32 = 12;
+const JOURNAL_END: u32 = 24;
+
+// Syscall numbers are literals rather than `SYS_*` so that nothing here depends on names user
+// code can see; `__neo_io` is reserved.
+const SHIM: &str = "
+.globl __neo_syscall, __neo_sys_unknown, __neo_sys_journal_full, __neo_sys_print
+__neo_syscall:
+    sw    t6, -8(x0)
+    sw    a0, -12(x0)
+    addi  t6, x0, 0
+    beq   a7, t6, __neo_sys_halt
+    addi  t6, x0, 1
+    beq   a7, t6, __neo_sys_read
+    addi  t6, x0, 2
+    beq   a7, t6, __neo_sys_commit
+    addi  t6, x0, 3
+    beq   a7, t6, __neo_sys_print
+__neo_sys_unknown:
+    halt
+__neo_sys_halt:
+    li    t6, __neo_io
+    sw    a0, 12(t6)
+    halt
+__neo_sys_read:
+    li    t6, __neo_io
+    lw    a0, 0(t6)
+    lw    a1, 4(t6)
+    bgeu  a0, a1, __neo_sys_eof
+    addi  a1, a0, 4
+    sw    a1, 0(t6)
+    lw    a0, 0(a0)
+    addi  a1, x0, 1
+    j     __neo_sys_return
+__neo_sys_eof:
+    addi  a0, x0, 0
+    addi  a1, x0, 0
+    j     __neo_sys_return
+__neo_sys_commit:
+    li    t6, __neo_io
+    lw    a0, 24(t6)
+    lw    t6, 8(t6)
+    bgeu  t6, a0, __neo_sys_journal_full
+    lw    a0, -12(x0)
+    sw    a0, 0(t6)
+    addi  a0, t6, 4
+    li    t6, __neo_io
+    sw    a0, 8(t6)
+    lw    a0, -12(x0)
+    j     __neo_sys_return
+__neo_sys_journal_full:
+    halt
+__neo_sys_print:
+    lw    t6, -8(x0)
+    jalr  x0, 0(t6)
+__neo_sys_return:
+    lw    t6, -8(x0)
+    jalr  x0, 0(t6)
+";
+
+fn default_journal_words() -> usize {
+    64
+}
+
+/// `syscalls` config: enables the ABI for an assembled guest.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct Rv32SyscallConfig {
+    /// Input stream `SYS_READ` returns, in order. Left out of the public statement, but a
+    /// verifier needs the same stream in its config (see the module docs).
+    #[serde(default)]
+    pub(crate) input: Vec<u32>,
+    /// Journal capacity; a `SYS_COMMIT` past it stops the guest with `journal_full`.
+    #[serde(default = "default_journal_words")]
+    pub(crate) journal_words: usize,
+}
+
+/// Placement of the I/O block.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rv32Io {
+    pub(crate) base: u64,
+    journal_words: u64,
+    input_words: u64,
+}
+
+impl Rv32Io {
+    pub(crate) fn new(base: u64, config: &Rv32SyscallConfig) -> Self {
+        Rv32Io {
+            base,
+            journal_words: config.journal_words as u64,
+            input_words: config.input.len() as u64,
+        }
+    }
+
+    /// Words the block spans: header, journal and input.
+    pub(crate) fn words(&self) -> usize {
+        (HEADER_BYTES as u64 / 4 + self.journal_words + self.input_words) as usize
+    }
+
+    /// Byte range the block spans.
+    pub(crate) fn range(&self) -> Range<u64> {
+        self.base..self.base + 4 * self.words() as u64
+    }
+
+    fn addr(&self, offset: u32) -> u32 {
+        (self.base as u32).wrapping_add(offset)
+    }
+
+    fn journal_start(&self) -> u32 {
+        self.addr(HEADER_BYTES)
+    }
+
+    fn input_start(&self) -> u32 {
+        self.journal_start().wrapping_add(4 * self.journal_words as u32)
+    }
+
+    /// Assembler constants: `__neo_io` and the `SYS_*` numbers.
+    pub(crate) fn symbols(&self) -> Vec<(String, i64)> {
+        vec![
+            ("__neo_io".into(), self.base as i64),
+            ("SYS_HALT".into(), SYS_HALT as i64),
+            ("SYS_READ".into(), SYS_READ as i64),
+            ("SYS_COMMIT".into(), SYS_COMMIT as i64),
+            ("SYS_PRINT".into(), SYS_PRINT as i64),
+        ]
+    }
+
+    pub(crate) fn shim() -> Rv32EcallShim {
+        Rv32EcallShim {
+            entry: "__neo_syscall",
+            source: SHIM,
+            t6_slot: SAVED_T6,
+        }
+    }
+
+    /// Header and input words the guest starts with.
+    pub(crate) fn init(&self, input: &[u32]) -> Vec<(u32, u32)> {
+        let input_end = self.input_start().wrapping_add(4 * input.len() as u32);
+        let mut words = vec![
+            (self.addr(INPUT_CURSOR), self.input_start()),
+            (self.addr(INPUT_END), input_end),
+            (self.addr(JOURNAL_CURSOR), self.journal_start()),
+            (self.addr(EXIT_CODE), 0),
+            (self.addr(JOURNAL_END), self.input_start()),
+        ];
+        words.extend(
+            input
+                .iter()
+                .enumerate()
+                .map(|(i, &value)| (self.input_start().wrapping_add(4 * i as u32), value)),
+        );
+        words
+    }
+
+    /// Exit code, journal and input consumption left in `machine`'s RAM.
+    pub(crate) fn report(&self, machine: &Rv32Machine) -> Rv32IoReport {
+        let words_from = |start: u32, cursor: u32, cap: u64| (cursor.saturating_sub(start) as u64 / 4).min(cap);
+        let journal_len = words_from(
+            self.journal_start(),
+            machine.read_mem(self.addr(JOURNAL_CURSOR), 4),
+            self.journal_words,
+        );
+        Rv32IoReport {
+            exit_code: machine.read_mem(self.addr(EXIT_CODE), 4),
+            input_read: words_from(
+                self.input_start(),
+                machine.read_mem(self.addr(INPUT_CURSOR), 4),
+                self.input_words,
+            ) as usize,
+            journal: (0..journal_len as u32)
+                .map(|i| machine.read_mem(self.journal_start().wrapping_add(4 * i), 4))
+                .collect(),
+            prints: machine.prints.clone(),
+        }
+    }
+
+    /// Output claims binding the exit code, the journal length and every journal word.
+    pub(crate) fn claims(&self, exit_code: u32, journal: &[u32]) -> Vec<Rv32OutputClaim> {
+        let journal_end = self.journal_start().wrapping_add(4 * journal.len() as u32);
+        let mut claims = vec![
+            Rv32OutputClaim {
+                addr: self.addr(EXIT_CODE) as u64,
+                expected: exit_code,
+            },
+            Rv32OutputClaim {
+                addr: self.addr(JOURNAL_CURSOR) as u64,
+                expected: journal_end,
+            },
+        ];
+        claims.extend(journal.iter().enumerate().map(|(i, &expected)| Rv32OutputClaim {
+            addr: self.journal_start().wrapping_add(4 * i as u32) as u64,
+            expected,
+        }));
+        claims
+    }
+}
+
+/// Shim entry points the interpreter watches for, located in an assembled program.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rv32SyscallShim {
+    pub(crate) io: Rv32Io,
+    pub(crate) print_pc: u32,
+    pub(crate) unknown_pc: u32,
+    pub(crate) journal_full_pc: u32,
+}
+
+impl Rv32SyscallShim {
+    /// `None` unless `program` was assembled with the shim linked.
+    pub(crate) fn locate(io: Rv32Io, program: &Rv32Program) -> Option<Self> {
+        let pc = |name: &str| program.symbols.iter().find(|(s, _)| s == name).map(|&(_, addr)| addr);
+        Some(Rv32SyscallShim {
+            io,
+            print_pc: pc("__neo_sys_print")?,
+            unknown_pc: pc("__neo_sys_unknown")?,
+            journal_full_pc: pc("__neo_sys_journal_full")?,
+        })
+    }
+
+    /// PC of the `ecall` being serviced, from the link the shim saved. The link points at the
+    /// `lw` restoring `t6`, two words past the start of the rewritten `ecall`.
+    pub(crate) fn caller(&self, machine: &Rv32Machine) -> u32 {
+        machine.read_mem(SAVED_LINK as u32, 4).wrapping_sub(8)
+    }
+}
+
+/// One `SYS_PRINT`.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32DebugPrint {
+    pub(crate) step: usize,
+    /// PC of the `ecall`.
+    pub(crate) pc: u32,
+    pub(crate) value: u32,
+}
+
+/// What a syscall-enabled guest did with its I/O.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32IoReport {
+    /// `a0` of the `SYS_HALT` call (0 if the guest never made one).
+    pub(crate) exit_code: u32,
+    /// Input words returned by `SYS_READ`.
+    pub(crate) input_read: usize,
+    /// Words committed with `SYS_COMMIT`, in order.
+    pub(crate) journal: Vec<u32>,
+    /// Native-only debug output; nothing here is proven.
+    pub(crate) prints: Vec<Rv32DebugPrint>,
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use neo_fold::riscv_trace_shard::Rv32TraceWiringRun;
+use tsify::Tsify;
+
+use crate::rv32_disasm::disassemble_word;
+use crate::rv32_exec::{Rv32MemAccess, Rv32RegRead, Rv32RegWrite, Rv32StepRecord};
+
+/// One row of the prover's execution table.
+///
+/// The prover's table exposes how many Shout lookups each row makes; the instruction, register
+/// and memory fields come from the native run of the same program, whose step `i` executes in
+/// table row `i`. Rows past the end of the native run are padding (`active: false`, zeroed).
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub(crate) struct Rv32TraceRow {
+    pub(crate) row: usize,
+    pub(crate) active: bool,
+    pub(crate) pc: u32,
+    pub(crate) next_pc: u32,
+    pub(crate) word: u32,
+    /// Disassembly of `word` (`None` for padding rows).
+    #[tsify(optional)]
+    pub(crate) text: Option<String>,
+    pub(crate) reg_reads: Vec<Rv32RegRead>,
+    #[tsify(optional)]
+    pub(crate) reg_write: Option<Rv32RegWrite>,
+    #[tsify(optional)]
+    pub(crate) mem: Option<Rv32MemAccess>,
+    /// Shout (lookup-table) events the prover recorded for this row.
+    pub(crate) shout_lookups: usize,
+}
+
+/// A page of execution-table rows.
+#[derive(Debug, Clone, serde::Serialize, Tsify)]
+pub struct Rv32TraceRows(pub(crate) Vec<Rv32TraceRow>);
+
+/// Shout events per row of `run`'s execution table.
+pub(crate) fn shout_lookups(run: &Rv32TraceWiringRun) -> Vec<usize> {
+    run.exec_table().rows.iter().map(|r| r.shout_events.len()).collect()
+}
+
+/// Rows `[offset, offset + limit)` of an execution table with `shout_lookups[i]` lookups in row
+/// `i`, filled in from the native run `native`.
+pub(crate) fn trace_rows(
+    shout_lookups: &[usize],
+    native: &[Rv32StepRecord],
+    offset: usize,
+    limit: usize,
+) -> Vec<Rv32TraceRow> {
+    let start = offset.min(shout_lookups.len());
+    let end = start.saturating_add(limit).min(shout_lookups.len());
+    (start..end)
+        .map(|i| match native.get(i) {
+            Some(step) => Rv32TraceRow {
+                row: i,
+                active: true,
+                pc: step.pc,
+                next_pc: step.next_pc,
+                word: step.word,
+                text: Some(disassemble_word(step.word, step.pc)),
+                reg_reads: step.reg_reads.clone(),
+                reg_write: step.reg_write.clone(),
+                mem: step.mem.clone(),
+                shout_lookups: shout_lookups[i],
+            },
+            None => Rv32TraceRow {
+                row: i,
+                active: false,
+                pc: 0,
+                next_pc: 0,
+                word: 0,
+                text: None,
+                reg_reads: Vec::new(),
+                reg_write: None,
+                mem: None,
+                shout_lookups: shout_lookups[i],
+            },
+        })
+        .collect()
+}
+
+/// CSV rendering of `rows` with a header line. Register reads/writes are `x<n>=<hex>` separated
+/// by spaces and memory accesses `R|W<width>@<addr>=<value>`; empty cells mean "none".
+pub(crate) fn trace_csv(rows: &[Rv32TraceRow]) -> String {
+    let mut out = String::from("row,active,pc,next_pc,word,text,reg_reads,reg_write,mem,shout_lookups\n");
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    for r in rows {
+        let reads = r
+            .reg_reads
+            .iter()
+            .map(|l| format!("x{}={:#x}", l.reg, l.value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let write = r
+            .reg_write
+            .as_ref()
+            .map(|w| format!("x{}={:#x}", w.reg, w.value))
+            .unwrap_or_default();
+        let mem = r
+            .mem
+            .as_ref()
+            .map(|m| format!("{}{}@{:#x}={:#x}", if m.write { "W" } else { "R" }, m.width, m.addr, m.value))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{},{},{:#x},{:#x},{:08x},{},{},{},{},{}",
+            r.row,
+            r.active,
+            r.pc,
+            r.next_pc,
+            r.word,
+            quote(r.text.as_deref().unwrap_or("")),
+            reads,
+            write,
+            mem,
+            r.shout_lookups,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::riscv_asm::{assemble_rv32, Rv32AsmLayout};
+    use crate::rv32_exec::Rv32Machine;
+
+    fn native_run(src: &str) -> Vec<Rv32StepRecord> {
+        let out = assemble_rv32(src, &HashMap::new(), &Rv32AsmLayout::default());
+        let program = out.program.unwrap_or_else(|| panic!("{:?}", out.diagnostics));
+        Rv32Machine::new(0, &program.rom, &program.ram_init).unwrap().run(100)
+    }
+
+    #[test]
+    fn rows_page_through_the_table_and_pad_past_the_native_run() {
+        let native = native_run("li a0, 5\nsw a0, 0x100(x0)\nlw a1, 0x100(x0)\necall\n");
+        assert_eq!(native.len(), 4);
+        let shouts = [1, 1, 1, 0, 0, 0];
+
+        let page = trace_rows(&shouts, &native, 1, 2);
+        assert_eq!(page.iter().map(|r| r.row).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(page[0].text.as_deref(), Some("sw a0, 256(zero)"));
+        let store = page[0].mem.as_ref().unwrap();
+        assert!(store.write && store.addr == 0x100 && store.value == 5);
+        assert_eq!(page[1].reg_write.as_ref().map(|w| (w.reg, w.value)), Some((11, 5)));
+
+        let tail = trace_rows(&shouts, &native, 3, usize::MAX);
+        assert_eq!(tail.len(), 3);
+        assert!(tail[0].active && !tail[1].active && !tail[2].active);
+        assert_eq!((tail[1].pc, tail[1].word, tail[1].text.as_deref()), (0, 0, None));
+
+        assert!(trace_rows(&shouts, &native, 6, 10).is_empty());
+        assert!(trace_rows(&shouts, &native, usize::MAX, usize::MAX).is_empty());
+        assert!(trace_rows(&shouts, &native, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn csv_has_one_line_per_row() {
+        let native = native_run("li a0, 5\nsw a0, 0x100(x0)\necall\n");
+        let csv = trace_csv(&trace_rows(&[0, 1, 0, 0], &native, 0, usize::MAX));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "row,active,pc,next_pc,word,text,reg_reads,reg_write,mem,shout_lookups");
+        assert_eq!(lines[2], "1,true,0x4,0x8,10a02023,\"sw a0, 256(zero)\",x0=0x0 x10=0x5,,W4@0x100=0x5,1");
+        assert_eq!(lines[4], "3,false,0x0,0x0,00000000,\"\",,,,0");
+    }
+}
@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+
+use js_sys::Date;
+use neo_spartan_bridge::{SpartanProverKey, SpartanVerifierKey};
+use sha2::{Digest, Sha256};
+
+use crate::error::NeoDemoError;
+
+/// Spartan keypair memoized for one (params, CCS, folding step count) combination.
+///
+/// `setup_fold_run` only depends on the circuit and the number of folding steps, so repeated
+/// proofs against the same circuit can skip it entirely.
+pub(crate) struct SpartanKeys {
+    pub(crate) id: [u8; 32],
+    pub(crate) pk: SpartanProverKey,
+    pub(crate) vk: SpartanVerifierKey,
+    /// Time spent in `setup_fold_run` when these keys were created (0 for imported keys).
+    pub(crate) setup_ms: f64,
+}
+
+/// Serialized `(pk, vk)` bundle, tagged with the cache id it was produced for.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SpartanKeysExport {
+    id: [u8; 32],
+    pk: SpartanProverKey,
+    vk: SpartanVerifierKey,
+}
+
+/// SHA-256 over the bincode encoding of `(params, ccs)`.
+pub(crate) fn circuit_digest<P: serde::Serialize, C: serde::Serialize>(
+    params: &P,
+    ccs: &C,
+) -> Result<[u8; 32], NeoDemoError> {
+    let bytes = bincode::serialize(&(params, ccs))
+        .map_err(|e| NeoDemoError::serialize(format!("circuit digest error: {e}")))?;
+    Ok(Sha256::digest(&bytes).into())
+}
+
+/// Cache id for a keypair: the circuit digest plus the folding step count.
+pub(crate) fn spartan_keys_id(circuit_digest: &[u8; 32], step_count: usize) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(circuit_digest);
+    h.update((step_count as u64).to_le_bytes());
+    h.finalize().into()
+}
+
+impl SpartanKeys {
+    /// Run `setup` unless `slot` already holds keys for `id`.
+    ///
+    /// Returns the keys and whether they came from the cache.
+    pub(crate) fn get_or_setup<E>(
+        slot: &mut Option<SpartanKeys>,
+        id: [u8; 32],
+        setup: impl FnOnce() -> Result<(SpartanProverKey, SpartanVerifierKey), E>,
+    ) -> Result<(&SpartanKeys, bool), E> {
+        let cached = slot.as_ref().is_some_and(|k| k.id == id);
+        if !cached {
+            let start = Date::now();
+            let (pk, vk) = setup()?;
+            let setup_ms = Date::now() - start;
+            *slot = Some(SpartanKeys { id, pk, vk, setup_ms });
+        }
+        Ok((slot.as_ref().expect("spartan keys populated above"), cached))
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, NeoDemoError> {
+        let export = SpartanKeysExport {
+            id: self.id,
+            pk: self.pk.clone(),
+            vk: self.vk.clone(),
+        };
+        bincode::serialize(&export).map_err(|e| NeoDemoError::serialize(format!("serialize error: {e}")))
+    }
+
+    /// Load keys produced by `to_bytes()`, checking they match the expected cache `id`.
+    pub(crate) fn from_bytes(bytes: &[u8], id: [u8; 32]) -> Result<SpartanKeys, NeoDemoError> {
+        let export: SpartanKeysExport = bincode::deserialize(bytes)
+            .map_err(|e| NeoDemoError::parse(format!("deserialize error: spartan keys: {e}")))?;
+        if export.id != id {
+            return Err(NeoDemoError::shape("spartan keys were generated for a different circuit or step count"));
+        }
+        Ok(SpartanKeys {
+            id,
+            pk: export.pk,
+            vk: export.vk,
+            setup_ms: 0.0,
+        })
+    }
+}
+
+thread_local! {
+    /// Keys for the one-shot RV32 entry point, which has no session object to hold them.
+    pub(crate) static RV32_SPARTAN_KEYS: RefCell<Option<SpartanKeys>> = const { RefCell::new(None) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NeoDemoErrorKind;
+
+    #[test]
+    fn cache_ids_follow_the_circuit_and_step_count() {
+        let a = circuit_digest(&1u32, &vec![1u8, 2, 3]).unwrap();
+        let b = circuit_digest(&1u32, &vec![1u8, 2, 4]).unwrap();
+        assert_eq!(a, circuit_digest(&1u32, &vec![1u8, 2, 3]).unwrap());
+        assert_ne!(a, b);
+
+        let id = spartan_keys_id(&a, 4);
+        assert_eq!(id, spartan_keys_id(&a, 4));
+        assert_ne!(id, spartan_keys_id(&a, 5));
+        assert_ne!(id, spartan_keys_id(&b, 4));
+    }
+
+    #[test]
+    fn truncated_key_bundles_are_parse_errors() {
+        let id = spartan_keys_id(&[0; 32], 1);
+        let err = SpartanKeys::from_bytes(&id[..16], id).err().expect("truncated bundle");
+        assert_eq!(err.kind, NeoDemoErrorKind::Parse);
+        assert!(err.message.contains("spartan keys"), "{}", err.message);
+    }
+}
@@ -0,0 +1,149 @@
+//! TypeScript types for values handed to JS.
+//!
+//! Every result derives `Tsify`, which writes its interface into `neo_fold_demo.d.ts`, and is
+//! converted with `to_js` so an export's declared type is the one of the value it returns.
+//!
+//! The `NeoFoldSession`/`NeoFoldProof` summaries are `neo_fold::test_export` types without that
+//! derive, so they are mirrored here and copied over with `mirror`. Mirrors reject unknown and
+//! missing fields, so an upstream change fails the call with an error naming the field instead
+//! of silently disagreeing with the declarations.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::error::NeoDemoError;
+
+/// Convert `value` into its `Tsify` JS type.
+pub(crate) fn to_js<T: Tsify + Serialize>(value: &T) -> Result<T::JsType, JsValue> {
+    value
+        .into_js()
+        .map_err(|e| NeoDemoError::serialize(format!("serialize error: {e}")).into())
+}
+
+/// Copy the upstream value `value` into its mirror `M` and convert that into its JS type.
+pub(crate) fn mirror<M: Tsify + Serialize + DeserializeOwned>(
+    what: &str,
+    value: &impl Serialize,
+) -> Result<M::JsType, JsValue> {
+    let mirrored: M = serde_wasm_bindgen::to_value(value)
+        .and_then(serde_wasm_bindgen::from_value)
+        .map_err(|e| NeoDemoError::serialize(format!("serialize error: upstream {what} changed shape: {e}")))?;
+    to_js(&mirrored)
+}
+
+/// Per-step folding prove times in milliseconds.
+#[derive(Serialize, Tsify)]
+pub struct FoldStepMs(pub(crate) Vec<f64>);
+
+#[derive(Serialize, Deserialize, Tsify)]
+#[serde(deny_unknown_fields)]
+pub struct SetupTimingsMs {
+    ajtai_setup: f64,
+    build_ccs: f64,
+    session_init: f64,
+}
+
+#[derive(Serialize, Deserialize, Tsify)]
+#[serde(deny_unknown_fields)]
+pub struct ParamsSummary {
+    b: u32,
+    d: u32,
+    kappa: u32,
+    k_rho: u32,
+    #[serde(rename = "T")]
+    t: u32,
+    s: u32,
+    lambda: u32,
+}
+
+#[derive(Serialize, Deserialize, Tsify)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitSummary {
+    r1cs_constraints: usize,
+    r1cs_variables: usize,
+    r1cs_padded_n: usize,
+    r1cs_a_nnz: usize,
+    r1cs_b_nnz: usize,
+    r1cs_c_nnz: usize,
+    witness_steps: usize,
+    witness_fields_total: usize,
+    witness_fields_min: usize,
+    witness_fields_max: usize,
+    witness_nonzero_fields_total: usize,
+    witness_nonzero_ratio: f64,
+    ccs_n: usize,
+    ccs_m: usize,
+    ccs_t: usize,
+    ccs_max_degree: usize,
+    ccs_poly_terms: usize,
+    ccs_matrix_nnz: Vec<usize>,
+    ccs_matrix_nnz_total: usize,
+}
+
+#[derive(Serialize, Deserialize, Tsify)]
+#[serde(deny_unknown_fields)]
+pub struct ProofEstimate {
+    proof_steps: usize,
+    final_accumulator_len: usize,
+    fold_lane_commitments: usize,
+    mem_cpu_val_claim_commitments: usize,
+    val_lane_commitments: usize,
+    total_commitments: usize,
+    commitment_d: usize,
+    commitment_kappa: usize,
+    commitment_bytes: usize,
+    estimated_commitment_bytes: usize,
+}
+
+#[derive(Serialize, Deserialize, Tsify)]
+#[serde(deny_unknown_fields)]
+pub struct FoldingSummary {
+    k_in: Vec<usize>,
+    acc_len_after: Vec<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// `json` (an upstream summary as serialized) copies into `M` unchanged, and the copy fails,
+    /// naming the field, once a field is added or removed.
+    fn mirrors<M: Serialize + DeserializeOwned>(json: Value) {
+        let copied: M = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&copied).unwrap(), json);
+
+        let mut added = json.clone();
+        added["added_upstream"] = 1.into();
+        let err = serde_json::from_value::<M>(added).err().expect("unknown field");
+        assert!(err.to_string().contains("added_upstream"), "{err}");
+        for field in json.as_object().unwrap().keys() {
+            let mut removed = json.clone();
+            removed.as_object_mut().unwrap().remove(field);
+            let err = serde_json::from_value::<M>(removed).err().expect("missing field");
+            assert!(err.to_string().contains(field.as_str()), "{err}");
+        }
+    }
+
+    #[test]
+    fn mirrors_reject_upstream_shape_changes() {
+        mirrors::<SetupTimingsMs>(json!({ "ajtai_setup": 1.5, "build_ccs": 2.0, "session_init": 0.25 }));
+        mirrors::<ParamsSummary>(json!({ "b": 2, "d": 54, "kappa": 16, "k_rho": 12, "T": 216, "s": 2, "lambda": 127 }));
+        mirrors::<FoldingSummary>(json!({ "k_in": [1, 2], "acc_len_after": [2, 2] }));
+        mirrors::<ProofEstimate>(json!({
+            "proof_steps": 4,
+            "final_accumulator_len": 2,
+            "fold_lane_commitments": 8,
+            "mem_cpu_val_claim_commitments": 0,
+            "val_lane_commitments": 0,
+            "total_commitments": 8,
+            "commitment_d": 54,
+            "commitment_kappa": 16,
+            "commitment_bytes": 6912,
+            "estimated_commitment_bytes": 55296,
+        }));
+    }
+}
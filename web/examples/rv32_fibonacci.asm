@@ -6,7 +6,8 @@
 # - write result (u32) to RAM[0x100]
 # - halt via ecall
 #
-# Supported subset: addi, add, lw, sw, beq, jal, j, li (small), mv, ecall, nop
+# Supported: RV32I + RV32M instructions (labels on branches/jal) and the standard pseudo-instructions (li, la, call, ret, ...)
+#            .text/.data sections with .word/.half/.byte/.space/.align data and .equ constants
 
 # load n
 addi t0, x0, 0x104
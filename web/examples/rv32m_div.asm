@@ -0,0 +1,46 @@
+# RV32M divide/remainder demo (mini-asm)
+#
+# Exercises div, divu, rem, remu, including the RISC-V corner cases:
+# division by zero (quotient all ones, remainder = dividend) and signed overflow
+# (INT_MIN / -1 = INT_MIN, remainder 0).
+#
+# Prove with `prove_verify_rv32_asm` (or `Rv32Session`) using:
+#   ram_init: [{ addr: 0x200, value: 0xffffff9c },   # a = -100
+#              { addr: 0x204, value: 7 },            # b = 7
+#              { addr: 0x208, value: 0x80000000 }]   # INT_MIN
+#   outputs:  [{ addr: 0x100, expected: 0xfffffff2 },   # div   a / b   = -14
+#              { addr: 0x104, expected: 0x24924916 },   # divu  a / b   (unsigned)
+#              { addr: 0x108, expected: 0xfffffffe },   # rem   a % b   = -2
+#              { addr: 0x10c, expected: 0x00000002 },   # remu  a % b   (unsigned)
+#              { addr: 0x110, expected: 0xffffffff },   # div   a / 0
+#              { addr: 0x114, expected: 0xffffff9c },   # remu  a % 0   = a
+#              { addr: 0x118, expected: 0x80000000 },   # div   INT_MIN / -1
+#              { addr: 0x11c, expected: 0x00000000 }]   # rem   INT_MIN % -1
+
+addi t0, x0, 0x200
+lw   a0, 0(t0)        # a
+lw   a1, 4(t0)        # b
+lw   a2, 8(t0)        # INT_MIN
+addi a3, x0, -1
+
+addi t1, x0, 0x100
+
+div  t2, a0, a1
+sw   t2, 0(t1)
+divu t2, a0, a1
+sw   t2, 4(t1)
+rem  t2, a0, a1
+sw   t2, 8(t1)
+remu t2, a0, a1
+sw   t2, 12(t1)
+
+div  t2, a0, x0
+sw   t2, 16(t1)
+remu t2, a0, x0
+sw   t2, 20(t1)
+
+div  t2, a2, a3
+sw   t2, 24(t1)
+rem  t2, a2, a3
+sw   t2, 28(t1)
+ecall
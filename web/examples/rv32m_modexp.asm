@@ -0,0 +1,35 @@
+# RV32M modular exponentiation kernel (mini-asm)
+#
+# Computes base^exp mod m by square-and-multiply using mul + remu.
+# Keep m < 2^16 so every product fits in 32 bits.
+#
+# Prove with `prove_verify_rv32_asm` (or `Rv32Session`) using:
+#   ram_init: [{ addr: 0x200, value: 7 },        # base
+#              { addr: 0x204, value: 560 },      # exp
+#              { addr: 0x208, value: 561 }]      # m  (Carmichael number: 7^560 mod 561 = 1)
+#   outputs:  [{ addr: 0x100, expected: 1 }]
+
+addi t0, x0, 0x200
+lw   a0, 0(t0)        # base
+lw   a1, 4(t0)        # exp
+lw   a2, 8(t0)        # m
+
+addi a3, x0, 1        # result = 1
+remu a0, a0, a2       # base %= m
+
+loop:
+beq  a1, x0, done
+andi t1, a1, 1
+beq  t1, x0, square
+mul  a3, a3, a0       # result = result * base % m
+remu a3, a3, a2
+square:
+mul  a0, a0, a0       # base = base * base % m
+remu a0, a0, a2
+srli a1, a1, 1        # exp >>= 1
+jal  x0, loop
+
+done:
+addi t2, x0, 0x100
+sw   a3, 0(t2)
+ecall
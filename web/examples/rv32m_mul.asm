@@ -0,0 +1,26 @@
+# RV32M multiply demo (mini-asm)
+#
+# Exercises mul, mulh, mulhu, mulhsu on two u32 inputs.
+#
+# Prove with `prove_verify_rv32_asm` (or `Rv32Session`) using:
+#   ram_init: [{ addr: 0x200, value: 0xfffffff9 }, { addr: 0x204, value: 0x00012345 }]   # a = -7, b = 74565
+#   outputs:  [{ addr: 0x100, expected: 0xfff8091d },   # mul    (low 32 bits of a*b)
+#              { addr: 0x104, expected: 0xffffffff },   # mulh   (signed x signed, high 32 bits)
+#              { addr: 0x108, expected: 0x00012344 },   # mulhu  (unsigned x unsigned, high 32 bits)
+#              { addr: 0x10c, expected: 0xffffffff }]   # mulhsu (signed x unsigned, high 32 bits)
+
+addi t0, x0, 0x200
+lw   a0, 0(t0)        # a
+lw   a1, 4(t0)        # b
+
+mul    a2, a0, a1
+mulh   a3, a0, a1
+mulhu  a4, a0, a1
+mulhsu a5, a0, a1
+
+addi t1, x0, 0x100
+sw   a2, 0(t1)
+sw   a3, 4(t1)
+sw   a4, 8(t1)
+sw   a5, 12(t1)
+ecall
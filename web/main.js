@@ -802,7 +802,18 @@ async function run() {
         }
         if (msg.type === "error") {
           cleanup();
-          reject(new Error(msg.error ?? "Unknown worker error"));
+          const message = msg.error ?? "Unknown worker error";
+          // Rebuild a real NeoDemoError so `instanceof` works on this side of the worker.
+          const NeoDemoError = window.__neo_fold_wasm?.neo_demo_error_class?.();
+          const err =
+            msg.kind && NeoDemoError
+              ? new NeoDemoError(msg.kind, message, msg.step, msg.line)
+              : Object.assign(new Error(message), {
+                  kind: msg.kind,
+                  step: msg.step,
+                  line: msg.line,
+                });
+          reject(err);
           return;
         }
       };
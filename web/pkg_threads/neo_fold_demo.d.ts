@@ -1,194 +0,0 @@
-/* tslint:disable */
-/* eslint-disable */
-
-/**
- * Opaque proof handle returned by `NeoFoldSession::fold_and_prove()`.
- */
-export class NeoFoldProof {
-    private constructor();
-    free(): void;
-    [Symbol.dispose](): void;
-    fold_step_ms(): any;
-    folding_summary(): any;
-    proof_estimate(): any;
-    step_count(): number;
-}
-
-/**
- * Stateful JS-facing session wrapper.
- *
- * Construct once from a circuit JSON, then:
- * - `add_step_*` incrementally
- * - `fold_and_prove()` to obtain an opaque proof handle
- * - `verify(proof)` to check it
- */
-export class NeoFoldSession {
-    free(): void;
-    [Symbol.dispose](): void;
-    /**
-     * Add one step from `(x, w)` encoded as JSON arrays of u64s.
-     */
-    add_step_io_json(x_json: string, w_json: string): void;
-    /**
-     * Add one step from a witness vector `z` encoded as JSON array of u64s.
-     */
-    add_step_witness_json(witness_json: string): void;
-    /**
-     * Add all witness steps from a full `TestExport` JSON (uses only `witness`).
-     */
-    add_steps_from_test_export_json(json: string): void;
-    circuit_summary(): any;
-    fold_and_prove(): NeoFoldProof;
-    /**
-     * Create a new session from a circuit JSON (same fields as `TestExport` but without `witness`).
-     *
-     * Note: serde ignores unknown fields, so passing a full `TestExport` JSON is also accepted.
-     */
-    constructor(circuit_json: string);
-    params_summary(): any;
-    /**
-     * Set verifier-side step-linking equality pairs from JSON.
-     *
-     * Format: `[[prev_idx, next_idx], ...]` (must be non-empty for multi-step verification).
-     */
-    set_step_linking_pairs_json(json: string): void;
-    setup_timings_ms(): any;
-    /**
-     * Compress a folding proof into a Spartan2 proof (Merkle-MLE engine).
-     */
-    spartan_prove(proof: NeoFoldProof): SpartanCompressedProof;
-    spartan_verify(proof: SpartanCompressedProof): boolean;
-    step_count(): number;
-    verify(proof: NeoFoldProof): boolean;
-}
-
-/**
- * Opaque Spartan proof handle returned by `NeoFoldSession::spartan_prove()`.
- */
-export class SpartanCompressedProof {
-    private constructor();
-    free(): void;
-    [Symbol.dispose](): void;
-    /**
-     * Downloadable Spartan proof bytes (SNARK proof only; excludes `vk`).
-     */
-    bytes(): Uint8Array;
-    /**
-     * Size of the downloadable artifact (SNARK proof only; excludes `vk`).
-     */
-    bytes_len(): number;
-    /**
-     * Size of the combined artifact (vk + snark).
-     *
-     * This is optional in the UI; when present it can be used to estimate vk size as
-     * `(vk+snark) - snark`.
-     */
-    vk_and_snark_bytes_len(): number;
-}
-
-export function initThreadPool(num_threads: number): Promise<any>;
-
-export function init_panic_hook(): void;
-
-export function init_thread_pool(num_threads: number): Promise<any>;
-
-/**
- * Prove+verify the RV32 Fibonacci program under the trace-wiring shared-bus circuit.
- *
- * Expected guest semantics:
- * - reads `n` from RAM[0x104] (u32)
- * - writes `fib(n)` to RAM[0x100] (u32)
- * - halts via `ecall` (treated as `Halt` in this VM)
- */
-export function prove_verify_rv32_trace_fibonacci_asm(asm: string, n: number, ram_bytes: number, chunk_size: number, max_steps: number, do_spartan: boolean): any;
-
-/**
- * Parse a `TestExport` JSON (same schema as `crates/deprecated-neo-fold/poseidon2-tests/*.json`),
- * then run prove+verify and return a small result object.
- */
-export function prove_verify_test_export_json(json: string): any;
-
-export class wbg_rayon_PoolBuilder {
-    private constructor();
-    free(): void;
-    [Symbol.dispose](): void;
-    build(): void;
-    mainJS(): string;
-    numThreads(): number;
-    receiver(): number;
-}
-
-export function wbg_rayon_start_worker(receiver: number): void;
-
-export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
-
-export interface InitOutput {
-    readonly __wbg_neofoldproof_free: (a: number, b: number) => void;
-    readonly __wbg_neofoldsession_free: (a: number, b: number) => void;
-    readonly __wbg_spartancompressedproof_free: (a: number, b: number) => void;
-    readonly init_panic_hook: () => void;
-    readonly init_thread_pool: (a: number) => any;
-    readonly neofoldproof_fold_step_ms: (a: number) => [number, number, number];
-    readonly neofoldproof_folding_summary: (a: number) => [number, number, number];
-    readonly neofoldproof_proof_estimate: (a: number) => [number, number, number];
-    readonly neofoldproof_step_count: (a: number) => number;
-    readonly neofoldsession_add_step_io_json: (a: number, b: number, c: number, d: number, e: number) => [number, number];
-    readonly neofoldsession_add_step_witness_json: (a: number, b: number, c: number) => [number, number];
-    readonly neofoldsession_add_steps_from_test_export_json: (a: number, b: number, c: number) => [number, number];
-    readonly neofoldsession_circuit_summary: (a: number) => [number, number, number];
-    readonly neofoldsession_fold_and_prove: (a: number) => [number, number, number];
-    readonly neofoldsession_new: (a: number, b: number) => [number, number, number];
-    readonly neofoldsession_params_summary: (a: number) => [number, number, number];
-    readonly neofoldsession_set_step_linking_pairs_json: (a: number, b: number, c: number) => [number, number];
-    readonly neofoldsession_setup_timings_ms: (a: number) => [number, number, number];
-    readonly neofoldsession_spartan_prove: (a: number, b: number) => [number, number, number];
-    readonly neofoldsession_spartan_verify: (a: number, b: number) => [number, number, number];
-    readonly neofoldsession_step_count: (a: number) => number;
-    readonly neofoldsession_verify: (a: number, b: number) => [number, number, number];
-    readonly prove_verify_rv32_trace_fibonacci_asm: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => [number, number, number];
-    readonly prove_verify_test_export_json: (a: number, b: number) => [number, number, number];
-    readonly spartancompressedproof_bytes: (a: number) => [number, number];
-    readonly spartancompressedproof_bytes_len: (a: number) => number;
-    readonly spartancompressedproof_vk_and_snark_bytes_len: (a: number) => number;
-    readonly __wbg_wbg_rayon_poolbuilder_free: (a: number, b: number) => void;
-    readonly wbg_rayon_poolbuilder_build: (a: number) => void;
-    readonly wbg_rayon_poolbuilder_mainJS: (a: number) => any;
-    readonly wbg_rayon_poolbuilder_numThreads: (a: number) => number;
-    readonly wbg_rayon_poolbuilder_receiver: (a: number) => number;
-    readonly wbg_rayon_start_worker: (a: number) => void;
-    readonly initThreadPool: (a: number) => any;
-    readonly memory: WebAssembly.Memory;
-    readonly __wbindgen_malloc: (a: number, b: number) => number;
-    readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
-    readonly __wbindgen_exn_store: (a: number) => void;
-    readonly __externref_table_alloc: () => number;
-    readonly __wbindgen_externrefs: WebAssembly.Table;
-    readonly __wbindgen_free: (a: number, b: number, c: number) => void;
-    readonly __externref_table_dealloc: (a: number) => void;
-    readonly __wbindgen_thread_destroy: (a?: number, b?: number, c?: number) => void;
-    readonly __wbindgen_start: (a: number) => void;
-}
-
-export type SyncInitInput = BufferSource | WebAssembly.Module;
-
-/**
- * Instantiates the given `module`, which can either be bytes or
- * a precompiled `WebAssembly.Module`.
- *
- * @param {{ module: SyncInitInput, memory?: WebAssembly.Memory, thread_stack_size?: number }} module - Passing `SyncInitInput` directly is deprecated.
- * @param {WebAssembly.Memory} memory - Deprecated.
- *
- * @returns {InitOutput}
- */
-export function initSync(module: { module: SyncInitInput, memory?: WebAssembly.Memory, thread_stack_size?: number } | SyncInitInput, memory?: WebAssembly.Memory): InitOutput;
-
-/**
- * If `module_or_path` is {RequestInfo} or {URL}, makes a request and
- * for everything else, calls `WebAssembly.instantiate` directly.
- *
- * @param {{ module_or_path: InitInput | Promise<InitInput>, memory?: WebAssembly.Memory, thread_stack_size?: number }} module_or_path - Passing `InitInput` directly is deprecated.
- * @param {WebAssembly.Memory} memory - Deprecated.
- *
- * @returns {Promise<InitOutput>}
- */
-export default function __wbg_init (module_or_path?: { module_or_path: InitInput | Promise<InitInput>, memory?: WebAssembly.Memory, thread_stack_size?: number } | InitInput | Promise<InitInput>, memory?: WebAssembly.Memory): Promise<InitOutput>;
@@ -343,6 +343,13 @@ async function runProveVerify({ id, json, doSpartan, bundle, threads }) {
     let spartanVkBytesLen = undefined;
 
     if (doSpartan) {
+      phase(id, "Spartan2 setup…");
+      const spSetupMs =
+        typeof session.spartan_setup === "function" ? session.spartan_setup(foldProof) : undefined;
+      if (typeof spSetupMs === "number") {
+        log(id, `Spartan2: setup=${spSetupMs === 0 ? "cached" : fmtMs(spSetupMs)}`);
+      }
+
       log(id, "Compressing with Spartan2…");
       phase(id, "Compressing…");
       const spStart = performance.now();
@@ -438,6 +445,7 @@ async function runRv32Fibonacci({ id, asm, riscv, doSpartan, bundle, threads })
   const ramBytes = typeof riscv?.ram_bytes === "number" ? riscv.ram_bytes : null;
   const chunkSize = typeof riscv?.chunk_size === "number" ? riscv.chunk_size : null;
   const maxSteps = typeof riscv?.max_steps === "number" ? riscv.max_steps : 0;
+  const expected = typeof riscv?.expected === "number" ? riscv.expected : undefined;
 
   if (!src.trim()) throw new Error("No RISC-V program provided.");
   if (!Number.isFinite(n) || n < 0) throw new Error("Invalid riscv.n");
@@ -459,6 +467,7 @@ async function runRv32Fibonacci({ id, asm, riscv, doSpartan, bundle, threads })
       chunkSize,
       maxSteps,
       Boolean(doSpartan),
+      expected,
     );
     const totalMs = performance.now() - totalStart;
     return { result, totalMs };
@@ -496,10 +505,22 @@ async function runRv32Fibonacci({ id, asm, riscv, doSpartan, bundle, threads })
   if (result) {
     log(
       id,
-      `OK: verify_ok=${String(result.verify_ok)} expected=fib(${result.n})=${result.expected} folds=${result.folds} (total ${fmtMs(totalMs)})`,
+      `OK: verify_ok=${String(result.verify_ok)} output=RAM[0x100]=${result.output} (n=${result.n}) folds=${result.folds} (total ${fmtMs(totalMs)})`,
     );
+    if (typeof result.expected === "number") {
+      log(
+        id,
+        `Expected ${result.expected}: ${result.output_matches ? "matches" : "MISMATCH"}`,
+        result.output_matches ? undefined : "warn",
+      );
+    }
     log(id, `Timings: prove=${fmtMs(result.prove_ms)} verify=${fmtMs(result.verify_ms)}`);
     if (typeof result.trace_len === "number") log(id, `Trace length: ${result.trace_len} instructions`);
+    if (result.ram) {
+      const ram = result.ram;
+      const pct = typeof ram.utilization === "number" ? ` (${(ram.utilization * 100).toFixed(1)}% of ${ram.ram_bytes} bytes)` : "";
+      log(id, `RAM: used_words=${ram.used_words}${pct} init_words=${ram.init_words} accessed_words=${ram.accessed_words}`);
+    }
     log(
       id,
       `Circuit (CCS): constraints=${result.ccs_constraints} variables=${result.ccs_variables} shout_lookups=${String(result.shout_lookups ?? "?")}`,
@@ -518,7 +539,7 @@ async function runRv32Fibonacci({ id, asm, riscv, doSpartan, bundle, threads })
 
     log(
       id,
-      `Spartan2: prove=${fmtMs(result.spartan.prove_ms)} verify=${fmtMs(result.spartan.verify_ms)} ok=${String(result.spartan.verify_ok)} snark=${fmtBytes(result.spartan.snark_bytes)}`,
+      `Spartan2: setup=${result.spartan.setup_cached ? "cached" : fmtMs(result.spartan.setup_ms)} prove=${fmtMs(result.spartan.prove_ms)} verify=${fmtMs(result.spartan.verify_ms)} ok=${String(result.spartan.verify_ok)} snark=${fmtBytes(result.spartan.snark_bytes)}`,
     );
   }
 
@@ -559,6 +580,14 @@ self.addEventListener("message", async (ev) => {
       emit(id, { type: "done" });
     }
   } catch (e) {
-    emit(id, { type: "error", error: String(e) });
+    // NeoDemoError carries a stable `kind` and, when known, the step and source line;
+    // forward them all so the page can rebuild the error.
+    emit(id, {
+      type: "error",
+      error: String(e?.message ?? e),
+      kind: e?.kind,
+      step: e?.step,
+      line: e?.line,
+    });
   }
 });